@@ -1,30 +1,164 @@
-use std::collections::HashSet;
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::signed,
+};
+use std::collections::{HashMap, HashSet};
+
+const DAY: Day = Day::new(2018, 1);
 
 #[aoc_generator(day1)]
-pub fn input_generator(input: &str) -> Vec<i64> {
-    input
-        .lines()
-        .filter_map(|l| l.parse::<i64>().ok())
-        .collect()
+pub fn input_generator(input: &str) -> Result<Vec<i64>, AocError> {
+    DAY.parse_lines(input, signed)
 }
 
 #[aoc(day1, part1)]
-pub fn one(input: &[i64]) -> i64 {
-    input.iter().sum()
+pub fn one(input: &[i64]) -> Result<i64, AocError> {
+    Ok(input.iter().sum())
 }
 
 #[aoc(day1, part2)]
-pub fn two(input: &[i64]) -> i64 {
-    let mut frequencies = HashSet::with_capacity(100000);
-    let mut answer = 0;
-    input.iter().cycle().try_fold(0, |acc, x| {
-        let frequency = acc + x;
-        if !frequencies.insert(frequency) {
-            answer = frequency;
-            None
-        } else {
-            Some(frequency)
+pub fn two(input: &[i64]) -> Result<i64, AocError> {
+    first_repeat(input)
+        .map(|repeat| repeat.frequency)
+        .ok_or_else(|| DAY.error("the frequency never repeats"))
+}
+
+/// The first frequency the device reaches twice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Repeat {
+    pub frequency: i64,
+    /// How many changes were applied when it was reached again.
+    pub changes: usize,
+    /// The pass over the list, from 0, of the change that reached it again.
+    pub iteration: usize,
+    /// The position of that change in the list.
+    pub position: usize,
+}
+
+/// Finds the first repeated frequency without replaying the list until it
+/// shows up, or `None` if no frequency is ever reached twice.
+///
+/// Starting from 0, the frequencies seen in pass `c` are `c * drift + q` for
+/// each prefix sum `q` of the list (the first being 0), where `drift` is the
+/// sum of the whole list. With no drift every pass repeats the first one.
+/// Otherwise a repeat within the first pass comes before any other; after
+/// that, prefix sums `a` and `b` can only meet if they are congruent modulo
+/// the drift, and then `a` reaches `b` after `(b - a) / drift` passes. Only
+/// neighbours in each such group need checking, as those meet first.
+pub fn first_repeat(changes: &[i64]) -> Option<Repeat> {
+    let n = changes.len();
+    let prefixes: Vec<i64> = std::iter::once(0)
+        .chain(changes.iter().scan(0, |sum, change| {
+            *sum += change;
+            Some(*sum)
+        }))
+        .take(n)
+        .collect();
+    let drift: i64 = changes.iter().sum();
+    let repeat = |changes: usize, frequency: i64| Repeat {
+        frequency,
+        changes,
+        iteration: (changes - 1) / n,
+        position: (changes - 1) % n,
+    };
+
+    let mut seen = HashSet::new();
+    for (k, &q) in prefixes.iter().enumerate() {
+        if !seen.insert(q) {
+            return Some(repeat(k, q));
+        }
+    }
+    if drift == 0 {
+        // The last change of the first pass brings it back to 0.
+        return (n > 0).then(|| repeat(n, 0));
+    }
+
+    // Group the prefix sums by residue, ordered in the direction of drift.
+    let mut groups: HashMap<i64, Vec<(i64, usize)>> = HashMap::new();
+    for (k, &q) in prefixes.iter().enumerate() {
+        groups
+            .entry(q.rem_euclid(drift.abs()))
+            .or_default()
+            .push((q * drift.signum(), k));
+    }
+    groups
+        .values_mut()
+        .flat_map(|group| {
+            group.sort_unstable();
+            group
+                .windows(2)
+                .map(|pair| {
+                    let ((from, k), (to, _)) = (pair[0], pair[1]);
+                    let passes = ((to - from) / drift.abs()) as usize;
+                    (passes * n + k, to * drift.signum())
+                })
+                .collect::<Vec<_>>()
+        })
+        .min()
+        .map(|(changes, frequency)| repeat(changes, frequency))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Replays the list until a frequency repeats or `limit` changes pass.
+    fn replay(changes: &[i64], limit: usize) -> Option<Repeat> {
+        let mut seen = HashSet::new();
+        seen.insert(0);
+        let mut frequency = 0;
+        for (i, change) in changes.iter().cycle().take(limit).enumerate() {
+            frequency += change;
+            if !seen.insert(frequency) {
+                return Some(Repeat {
+                    frequency,
+                    changes: i + 1,
+                    iteration: i / changes.len(),
+                    position: i % changes.len(),
+                });
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn examples() {
+        assert_eq!(two(&[1, -1]), Ok(0));
+        assert_eq!(two(&[3, 3, 4, -2, -4]), Ok(10));
+        assert_eq!(two(&[-6, 3, 8, 5, -6]), Ok(5));
+        assert_eq!(two(&[7, 7, -2, -7, -4]), Ok(14));
+        assert_eq!(
+            first_repeat(&[3, 3, 4, -2, -4]),
+            Some(Repeat {
+                frequency: 10,
+                changes: 7,
+                iteration: 1,
+                position: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn never_repeats() {
+        assert!(two(&[1, 1]).is_err());
+        assert_eq!(first_repeat(&[]), None);
+        assert_eq!(first_repeat(&[-5, 2]), None);
+    }
+
+    #[test]
+    fn matches_replay() {
+        let lists: [&[i64]; 8] = [
+            &[0],
+            &[5, -5, 5],
+            &[1, 2, -4, 3],
+            &[-3, 10, -9, 4, -1],
+            &[7, -13, 4, 9, -8, 2],
+            &[2, 2, -3],
+            &[-2, -2, 3],
+            &[100, -99, -2],
+        ];
+        for list in lists.iter() {
+            assert_eq!(first_repeat(list), replay(list, 100_000), "{:?}", list);
         }
-    });
-    answer
+    }
 }
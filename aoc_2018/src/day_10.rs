@@ -1,66 +1,132 @@
-use std::collections::HashSet;
+use aoc_utils::{
+    error::{AocError, Day},
+    grid::Grid,
+    ocr,
+    parse::{bracketed, coordinates, labeled},
+};
+use nom::{combinator::map, sequence::pair};
+use std::fmt::{self, Display};
+
+const DAY: Day = Day::new(2018, 10);
 
 #[aoc_generator(day10)]
-fn generator(input: &str) -> Vec<Star> {
-    input
-        .lines()
-        .map(|line| Star {
-            position: Vector {
-                x: line[10..16].trim_start().parse().expect("1"),
-                y: line[18..24].trim_start().parse().expect("2"),
-            },
-            velocity: Vector {
-                x: line[36..38].trim_start().parse().expect("3"),
-                y: line[40..42].trim_start().parse().expect("4"),
-            },
-        })
-        .collect()
+fn generator(input: &str) -> Result<Vec<Star>, AocError> {
+    let vector = || map(bracketed('<', coordinates, '>'), |(x, y)| Vector { x, y });
+    let star = pair(
+        labeled("position=", vector()),
+        labeled("velocity=", vector()),
+    );
+    DAY.parse_lines(
+        input,
+        map(star, |(position, velocity)| Star { position, velocity }),
+    )
 }
 
 #[aoc(day10, part1)]
-fn part1(input: &[Star]) -> usize {
-    let mut stars: Vec<Star> = input.iter().cloned().collect();
+fn part1(input: &[Star]) -> Result<String, AocError> {
+    let message = solve(input)?;
+    ocr::read(&message.sky).map_err(|e| DAY.error(e))
+}
 
-    let mut map = HashSet::new();
+#[aoc(day10, part2)]
+fn part2(input: &[Star]) -> Result<usize, AocError> {
+    Ok(solve(input)?.time)
+}
 
-    let mut y_min = stars.iter().map(|s| s.position.y).min().unwrap();
-    let mut y_max = stars.iter().map(|s| s.position.y).max().unwrap();
-    let mut x_min = stars.iter().map(|s| s.position.x).min().unwrap();
-    let mut x_max = stars.iter().map(|s| s.position.x).max().unwrap();
+/// The sky at the moment the stars line up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Message {
+    /// Seconds until the message appears.
+    time: usize,
+    /// The stars' bounding box at that moment, lit where a star is.
+    sky: Grid<bool>,
+}
 
-    let mut area = (x_max - x_min) * (y_max - y_min);
-    let mut new_area = area;
+impl Display for Message {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.sky.render(|&lit| if lit { '#' } else { '.' }).fmt(fmt)
+    }
+}
 
-    let mut i = 0;
-    while new_area <= area {
-        area = new_area;
-        for star in stars.iter_mut() {
-            star.step();
+/// Finds the second at which the stars are closest together.
+///
+/// The width and height of the stars' bounding box are each the gap between
+/// the largest and smallest of some lines over time, so both are convex and
+/// so is their sum. That makes the first second whose sum is no larger than
+/// the next one's the minimum, which a binary search over the slope finds
+/// after doubling its way to an upper bound.
+fn solve(input: &[Star]) -> Result<Message, AocError> {
+    if input.is_empty() {
+        return Err(DAY.error("there are no stars in the sky"));
+    }
+    let extent = |t: usize| {
+        let (min, max) = bounds(input, t);
+        (max.x - min.x) + (max.y - min.y)
+    };
+    let rising = |t: usize| extent(t + 1) >= extent(t);
+
+    let mut high = 1;
+    while !rising(high) {
+        high *= 2;
+    }
+    let mut low = 0;
+    while low < high {
+        let mid = (low + high) / 2;
+        if rising(mid) {
+            high = mid;
+        } else {
+            low = mid + 1;
         }
-        y_min = stars.iter().map(|s| s.position.y).min().unwrap();
-        y_max = stars.iter().map(|s| s.position.y).max().unwrap();
-        x_min = stars.iter().map(|s| s.position.x).min().unwrap();
-        x_max = stars.iter().map(|s| s.position.x).max().unwrap();
-        new_area = (x_max - x_min) * (y_max - y_min);
-        i += 1;
     }
 
-    for star in stars.iter_mut() {
-        star.leap(-1);
-        map.insert((star.position.x, star.position.y));
+    let time = low;
+    let (min, max) = bounds(input, time);
+    let mut sky = Grid::new(
+        (max.x - min.x + 1) as usize,
+        (max.y - min.y + 1) as usize,
+        false,
+    );
+    for position in positions(input, time) {
+        sky[((position.x - min.x) as usize, (position.y - min.y) as usize)] = true;
     }
+    Ok(Message { time, sky })
+}
 
-    for y in y_min..=y_max {
-        for x in x_min..=x_max {
-            if map.contains(&(x, y)) {
-                print!("#");
-            } else {
-                print!(".");
-            }
-        }
-        println!();
-    }
-    i - 1
+/// Where the stars are after `time` seconds.
+fn positions(stars: &[Star], time: usize) -> impl Iterator<Item = Vector> + '_ {
+    stars.iter().map(move |&star| {
+        let mut star = star;
+        star.leap(time as isize);
+        star.position
+    })
+}
+
+/// The top left and bottom right corners of the stars after `time` seconds.
+fn bounds(stars: &[Star], time: usize) -> (Vector, Vector) {
+    positions(stars, time).fold(
+        (
+            Vector {
+                x: isize::MAX,
+                y: isize::MAX,
+            },
+            Vector {
+                x: isize::MIN,
+                y: isize::MIN,
+            },
+        ),
+        |(min, max), p| {
+            (
+                Vector {
+                    x: min.x.min(p.x),
+                    y: min.y.min(p.y),
+                },
+                Vector {
+                    x: max.x.max(p.x),
+                    y: max.y.max(p.y),
+                },
+            )
+        },
+    )
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -70,10 +136,6 @@ struct Star {
 }
 
 impl Star {
-    fn step(&mut self) {
-        self.position += self.velocity;
-    }
-
     fn leap(&mut self, n: isize) {
         self.position += &self.velocity * n;
     }
@@ -109,3 +171,78 @@ impl std::ops::Mul<isize> for &Vector {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+position=< 9,  1> velocity=< 0,  2>
+position=< 7,  0> velocity=<-1,  0>
+position=< 3, -2> velocity=<-1,  1>
+position=< 6, 10> velocity=<-2, -1>
+position=< 2, -4> velocity=< 2,  2>
+position=<-6, 10> velocity=< 2, -2>
+position=< 1,  8> velocity=< 1, -1>
+position=< 1,  7> velocity=< 1,  0>
+position=<-3, 11> velocity=< 1, -2>
+position=< 7,  6> velocity=<-1, -1>
+position=<-2,  3> velocity=< 1,  0>
+position=<-4,  3> velocity=< 2,  0>
+position=<10, -3> velocity=<-1,  1>
+position=< 5, 11> velocity=< 1, -2>
+position=< 4,  7> velocity=< 0, -1>
+position=< 8, -2> velocity=< 0,  1>
+position=<15,  0> velocity=<-2,  0>
+position=< 1,  6> velocity=< 1,  0>
+position=< 8,  9> velocity=< 0, -1>
+position=< 3,  3> velocity=<-1,  1>
+position=< 0,  5> velocity=< 0, -1>
+position=<-2,  2> velocity=< 2,  0>
+position=< 5, -2> velocity=< 1,  2>
+position=< 1,  4> velocity=< 2,  1>
+position=<-2,  7> velocity=< 2, -2>
+position=< 3,  6> velocity=<-1, -1>
+position=< 5,  0> velocity=< 1,  0>
+position=<-6,  0> velocity=< 2,  0>
+position=< 5,  9> velocity=< 1, -2>
+position=<14,  7> velocity=<-2,  0>
+position=<-3,  6> velocity=< 2, -1>
+";
+
+    #[test]
+    fn example() {
+        let stars = generator(EXAMPLE).unwrap();
+        let message = solve(&stars).unwrap();
+        assert_eq!(message.time, 3);
+        assert_eq!(
+            message.to_string(),
+            "\
+#...#..###
+#...#...#.
+#...#...#.
+#####...#.
+#...#...#.
+#...#...#.
+#...#...#.
+#...#..###
+"
+        );
+        assert_eq!(part2(&stars), Ok(3));
+    }
+
+    #[test]
+    fn parser() {
+        let stars = generator("position=<-31684,   21234> velocity=<  3,-2>\n").unwrap();
+        assert_eq!(
+            stars[0].position,
+            Vector {
+                x: -31684,
+                y: 21234
+            }
+        );
+        assert_eq!(stars[0].velocity, Vector { x: 3, y: -2 });
+        assert!(generator("position=<1, 2>\n").is_err());
+        assert!(solve(&[]).is_err());
+    }
+}
@@ -1,75 +1,65 @@
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::{signed, ws},
+    summed_area::SummedAreaTable,
+};
 use std::fmt::{self, Display};
 
+const DAY: Day = Day::new(2018, 11);
+const GRID_SIZE: usize = 300;
+
 #[aoc_generator(day11)]
-fn generator(input: &str) -> [[isize; 300]; 300] {
-    let mut grid: [[isize; 300]; 300] = [[0; 300]; 300];
-    let grid_serial_number: isize = input.parse().unwrap();
-    for y in 0..300 {
-        for x in 0..300 {
-            let rack_id = x as isize + 10;
-            let power_level =
-                (((((rack_id * (y as isize)) + grid_serial_number) * rack_id) / 100) % 10) - 5;
-            grid[y][x] = power_level;
-        }
-    }
-    grid
+fn generator(input: &str) -> Result<Box<SummedAreaTable<isize>>, AocError> {
+    let serial: isize = DAY.parse_with(input, ws(signed))?;
+    Ok(Box::new(power_grid(serial, GRID_SIZE)))
 }
 
 #[aoc(day11, part1)]
-fn part1(power_cells: &[[isize; 300]]) -> Square {
-    let mut max: isize = 0;
-    let mut max_cell = Square {
-        x: 0,
-        y: 0,
-        size: 3,
-    };
-    for y in 0..298 {
-        for x in 0..298 {
-            let mut sum = 0;
-            for i in 0..3 {
-                for j in 0..3 {
-                    sum += power_cells[y + i][x + j];
-                }
-            }
-            if sum > max {
-                max = sum;
-                max_cell.x = x;
-                max_cell.y = y;
-            }
-        }
-    }
-    max_cell
+fn part1(power_cells: &SummedAreaTable<isize>) -> Result<String, AocError> {
+    let square = best_square(power_cells, 3)
+        .ok_or_else(|| DAY.error("the grid is smaller than 3x3"))?
+        .0;
+    Ok(format!("{},{}", square.x, square.y))
 }
 
 #[aoc(day11, part2)]
-fn part2(power_cells: &[[isize; 300]]) -> Square {
-    let mut max: isize = 0;
-    let mut max_cell = Square {
-        x: 0,
-        y: 0,
-        size: 0,
-    };
-    for size in 1..=300 {
-        for y in 0..=(300 - size) {
-            for x in 0..=(300 - size) {
-                let mut sum = 0;
-                for i in 0..size {
-                    for j in 0..size {
-                        sum += power_cells[y + i][x + j];
-                    }
-                }
-                if sum > max {
-                    max = sum;
-                    max_cell.x = x;
-                    max_cell.y = y;
-                    max_cell.size = size;
-                }
-            }
-        }
-    }
-    max_cell
+fn part2(power_cells: &SummedAreaTable<isize>) -> Result<Square, AocError> {
+    (1..=power_cells.width().min(power_cells.height()))
+        .filter_map(|size| best_square(power_cells, size))
+        .max_by_key(|&(_, power)| power)
+        .map(|(square, _)| square)
+        .ok_or_else(|| DAY.error("the grid is empty"))
 }
 
+/// The fuel cells of a `size` by `size` grid, numbered from 1 like the
+/// puzzle does.
+fn power_grid(serial: isize, size: usize) -> SummedAreaTable<isize> {
+    SummedAreaTable::from_fn(size, size, |(x, y)| {
+        power_level(serial, x as isize + 1, y as isize + 1)
+    })
+}
+
+fn power_level(serial: isize, x: isize, y: isize) -> isize {
+    let rack_id = x + 10;
+    ((rack_id * y + serial) * rack_id / 100) % 10 - 5
+}
+
+/// The `size` by `size` square with the most power, and that power.
+fn best_square(power_cells: &SummedAreaTable<isize>, size: usize) -> Option<(Square, isize)> {
+    power_cells
+        .rectangles(size, size)
+        .max_by_key(|&((x, y), power)| (power, std::cmp::Reverse((y, x))))
+        .map(|((x, y), power)| {
+            let square = Square {
+                x: x + 1,
+                y: y + 1,
+                size,
+            };
+            (square, power)
+        })
+}
+
+#[derive(Debug, PartialEq, Eq)]
 struct Square {
     x: usize,
     y: usize,
@@ -78,6 +68,31 @@ struct Square {
 
 impl Display for Square {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "({}, {}, {})", self.x, self.y, self.size)
+        write!(fmt, "{},{},{}", self.x, self.y, self.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_levels() {
+        assert_eq!(power_level(8, 3, 5), 4);
+        assert_eq!(power_level(57, 122, 79), -5);
+        assert_eq!(power_level(39, 217, 196), 0);
+        assert_eq!(power_level(71, 101, 153), 4);
+    }
+
+    #[test]
+    fn squares() {
+        let grid = generator("18").unwrap();
+        assert_eq!(part1(&grid), Ok("33,45".to_owned()));
+        assert_eq!(best_square(&grid, 3).unwrap().1, 29);
+        assert_eq!(part2(&grid).unwrap().to_string(), "90,269,16");
+
+        let grid = generator("42").unwrap();
+        assert_eq!(part1(&grid), Ok("21,61".to_owned()));
+        assert_eq!(part2(&grid).unwrap().to_string(), "232,251,12");
     }
 }
@@ -1,14 +1,25 @@
-use std::{fmt::{self, Display}, collections::{HashMap, HashSet}};
-use itertools::Itertools;
+use aoc_utils::{
+    error::{AocError, Day},
+    grid::Grid,
+    parse::char_grid,
+};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+const DAY: Day = Day::new(2018, 13);
 
 #[derive(Debug)]
 struct DataStore {
-    track: Vec<Vec<Option<Track>>>,
+    track: Grid<Option<Track>>,
     carts: Vec<Cart>,
 }
 
 #[derive(Debug, Clone)]
 struct Cart {
+    /// The cart's place in reading order on the initial track.
+    id: usize,
     x: usize,
     y: usize,
     direction: Direction,
@@ -24,7 +35,7 @@ enum Track {
     CurveRight,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Direction {
     North,
     South,
@@ -32,7 +43,7 @@ enum Direction {
     East,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Turn {
     Left,
     Straight,
@@ -40,85 +51,203 @@ enum Turn {
 }
 
 #[aoc_generator(day13)]
-fn generator(input: &str) -> Box<DataStore> {
-    let mut puzzle_input = Box::new(DataStore {
-        track: Vec::new(),
-        carts: Vec::new(),
-    });
-    
-    input.lines().enumerate().for_each(|(y, line)| {
-        puzzle_input.track.push(Vec::new());
-       line.chars().map(parse).enumerate().for_each(|(x, cell)| {
-           puzzle_input.track[y].push(cell.1);
-           if let Some(direction) = cell.0 {
-               puzzle_input.carts.push(Cart {x, y, direction, turn: Turn::Left});
-           }
-       })
-    });
-    puzzle_input
+fn generator(input: &str) -> Result<Box<DataStore>, AocError> {
+    let cells = DAY.parse_with(input, char_grid(parse))?;
+    let carts: Vec<Cart> = cells
+        .enumerate()
+        .filter_map(|((x, y), cell)| cell.0.map(|direction| (x, y, direction)))
+        .enumerate()
+        .map(|(id, (x, y, direction))| Cart {
+            id,
+            x,
+            y,
+            direction,
+            turn: Turn::Left,
+        })
+        .collect();
+    if carts.is_empty() {
+        return Err(DAY.error("there are no carts on the track"));
+    }
+    Ok(Box::new(DataStore {
+        track: cells.map(|cell| cell.1),
+        carts,
+    }))
 }
 
 #[aoc(day13, part1)]
-fn first_collision(data: &DataStore) -> Cart {
-    let mut carts = data.carts.clone();
-    let mut locations = HashSet::new();
-    loop {
-        for cart in carts.iter_mut().sorted_by_key(|c| c.y) {
-            cart.step();
-            if !locations.insert((cart.x, cart.y)) {
-                return cart.clone();
-            }
-            let track = data.track[cart.y][cart.x].expect("Carts flew off the track");
-            cart.turn(track);
-        }
-        locations.clear();
+fn first_collision(data: &DataStore) -> Result<String, AocError> {
+    if data.carts.len() < 2 {
+        return Err(DAY.error("a single cart has nothing to collide with"));
     }
+    let mut simulation = CartSimulation::new(data);
+    simulation.run_until(|s| !s.crashes().is_empty())?;
+    let crash = &simulation.crashes()[0];
+    Ok(format!("{},{}", crash.x, crash.y))
 }
 
 #[aoc(day13, part2)]
-fn last_cart(data: &DataStore) -> Cart {
-    let mut carts = data.carts.clone();
-    let mut locations = HashMap::new();
-    let mut collisions = Vec::new();
-    loop {
-        if carts.len() == 1 {
-            return carts[0].clone();
+fn last_cart(data: &DataStore) -> Result<String, AocError> {
+    let mut simulation = CartSimulation::new(data);
+    simulation.run_until(|s| s.carts().len() < 2)?;
+    match simulation.carts() {
+        [cart] => Ok(format!("{},{}", cart.x, cart.y)),
+        _ => Err(DAY.error("every cart crashed")),
+    }
+}
+
+/// Two carts running into each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Crash {
+    /// The tick it happened in, counting from 1.
+    tick: usize,
+    x: usize,
+    y: usize,
+    /// The ids of the cart that moved and the one it hit.
+    carts: (usize, usize),
+}
+
+/// Carts running around a track, one tick at a time.
+#[derive(Debug, Clone)]
+struct CartSimulation<'a> {
+    track: &'a Grid<Option<Track>>,
+    /// The carts still running, in reading order as of the last tick.
+    carts: Vec<Cart>,
+    ticks: usize,
+    crashes: Vec<Crash>,
+}
+
+impl<'a> CartSimulation<'a> {
+    fn new(data: &'a DataStore) -> Self {
+        CartSimulation {
+            track: &data.track,
+            carts: data.carts.clone(),
+            ticks: 0,
+            crashes: Vec::new(),
         }
-        for (i, cart) in carts.iter_mut().enumerate().sorted_by(|(_, a), (_, b)| a.y.cmp(&b.y).then(a.x.cmp(&b.x))) {
-            cart.step();
-            if let Some(j) = locations.insert((cart.x, cart.y), i) {
-                collisions.push(i);
-                collisions.push(j);
-                locations.remove(&(cart.x, cart.y));
+    }
+
+    fn carts(&self) -> &[Cart] {
+        &self.carts
+    }
+
+    /// Every crash so far, in the order they happened.
+    fn crashes(&self) -> &[Crash] {
+        &self.crashes
+    }
+
+    /// Where every cart is, where it's heading and how it turns next, in
+    /// reading order. The carts' future depends on nothing else.
+    fn state(&self) -> Vec<(usize, usize, Direction, Turn)> {
+        let mut state: Vec<_> = self
+            .carts
+            .iter()
+            .map(|c| (c.y, c.x, c.direction, c.turn))
+            .collect();
+        state.sort_unstable_by_key(|&(y, x, ..)| (y, x));
+        state
+    }
+
+    /// Ticks until `done` holds. Fails if the carts get back to a state they
+    /// were in before without that, as they would then go round forever,
+    /// which is spotted by comparing against a snapshot retaken after twice
+    /// as many ticks each time.
+    fn run_until<F: Fn(&Self) -> bool>(&mut self, done: F) -> Result<(), AocError> {
+        let (mut snapshot, mut since, mut period) = (self.state(), 0, 1);
+        while !done(self) {
+            self.tick()?;
+            let state = self.state();
+            if state == snapshot {
+                return Err(DAY.error("the carts go round forever without crashing"));
+            }
+            since += 1;
+            if since == period {
+                snapshot = state;
+                since = 0;
+                period *= 2;
             }
-            let track = data.track[cart.y][cart.x].expect("Cart flew off the track");
-            cart.turn(track);
         }
+        Ok(())
+    }
 
-        #[cfg(test)]
-        for y in 0..data.track.len() {
-            for x in 0..data.track[y].len() {
-                if let Some(idx) = locations.get(&(x, y)) {
-                    let c: char = carts[*idx].direction.into();
-                    //print!("{}", c);
-                } else {
-                    let c: char = data.track[y][x].map(|t| t.into()).unwrap_or(' ');
-                    //print!("{}", c);
+    /// Moves every cart once, top row first and left to right within a row.
+    /// Carts that crash are taken off the track straight away, so a cart
+    /// that has been hit never moves again, not even later in the same tick.
+    /// Returns the crashes of this tick.
+    fn tick(&mut self) -> Result<&[Crash], AocError> {
+        self.ticks += 1;
+        let first_crash = self.crashes.len();
+        self.carts.sort_by_key(|c| (c.y, c.x));
+        let mut occupied: HashMap<(usize, usize), usize> = self
+            .carts
+            .iter()
+            .enumerate()
+            .map(|(i, c)| ((c.x, c.y), i))
+            .collect();
+        let mut crashed = vec![false; self.carts.len()];
+
+        for i in 0..self.carts.len() {
+            if crashed[i] {
+                continue;
+            }
+            let cart = &mut self.carts[i];
+            occupied.remove(&(cart.x, cart.y));
+            cart.step()?;
+            match occupied.remove(&(cart.x, cart.y)) {
+                Some(j) => {
+                    crashed[i] = true;
+                    crashed[j] = true;
+                    self.crashes.push(Crash {
+                        tick: self.ticks,
+                        x: cart.x,
+                        y: cart.y,
+                        carts: (cart.id, self.carts[j].id),
+                    });
+                }
+                None => {
+                    occupied.insert((cart.x, cart.y), i);
+                    let track = track_at(self.track, cart)?;
+                    cart.turn(track);
                 }
             }
-            println!();
         }
 
-        collisions.sort_by_key(|i| -(*i as isize));
-        for i in collisions.drain(..) {
-            carts.remove(i);
+        let mut crashed = crashed.into_iter();
+        self.carts.retain(|_| !crashed.next().unwrap());
+        Ok(&self.crashes[first_crash..])
+    }
+}
+
+impl Display for CartSimulation<'_> {
+    /// The track with the carts drawn on it, and an `X` where carts crashed
+    /// during the last tick.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let mut picture = self.track.map(|track| track.map_or(' ', char::from));
+        for cart in &self.carts {
+            picture[(cart.x, cart.y)] = cart.direction.into();
         }
-        locations.clear();
+        for crash in self.crashes.iter().rev() {
+            if crash.tick != self.ticks {
+                break;
+            }
+            picture[(crash.x, crash.y)] = 'X';
+        }
+        for row in picture.rows() {
+            writeln!(fmt, "{}", row.iter().collect::<String>().trim_end())?;
+        }
+        Ok(())
     }
 }
 
-fn parse(cell: char) -> (Option<Direction>, Option<Track>) {
-    match cell {
+fn track_at(track: &Grid<Option<Track>>, cart: &Cart) -> Result<Track, AocError> {
+    track
+        .get((cart.x, cart.y))
+        .cloned()
+        .flatten()
+        .ok_or_else(|| DAY.error(format!("a cart flew off the track at {}", cart)))
+}
+
+fn parse(cell: char) -> Option<(Option<Direction>, Option<Track>)> {
+    Some(match cell {
         '^' => (Some(Direction::North), Some(Track::Vertical)),
         '>' => (Some(Direction::East), Some(Track::Horizontal)),
         'v' => (Some(Direction::South), Some(Track::Vertical)),
@@ -128,32 +257,35 @@ fn parse(cell: char) -> (Option<Direction>, Option<Track>) {
         '-' => (None, Some(Track::Horizontal)),
         '|' => (None, Some(Track::Vertical)),
         '+' => (None, Some(Track::Intersection)),
-        _ => (None, None),
-    }
+        ' ' => (None, None),
+        _ => return None,
+    })
 }
 
 impl Cart {
-    fn step(&mut self) {
-        match self.direction {
-            Direction::South => {
-                self.y += 1;
-            }
-            Direction::North => {
-                self.y -= 1;
-            }
-            Direction::West => {
-                self.x -= 1;
-            }
-            Direction::East => {
-                self.x += 1;
-            }
-        }
+    fn step(&mut self) -> Result<(), AocError> {
+        let (x, y) = (self.x, self.y);
+        let next = match self.direction {
+            Direction::South => Some((x, y + 1)),
+            Direction::North => y.checked_sub(1).map(|y| (x, y)),
+            Direction::West => x.checked_sub(1).map(|x| (x, y)),
+            Direction::East => Some((x + 1, y)),
+        };
+        let (x, y) =
+            next.ok_or_else(|| DAY.error(format!("a cart flew off the track at {}", self)))?;
+        self.x = x;
+        self.y = y;
+        Ok(())
     }
 
     fn turn(&mut self, track: Track) {
         self.direction = match track {
-            Track::CurveRight => self.direction.turn(self.direction.map_curve(Track::CurveRight)),
-            Track::CurveLeft => self.direction.turn(self.direction.map_curve(Track::CurveLeft)),
+            Track::CurveRight => self
+                .direction
+                .turn(self.direction.map_curve(Track::CurveRight)),
+            Track::CurveLeft => self
+                .direction
+                .turn(self.direction.map_curve(Track::CurveLeft)),
             Track::Intersection => {
                 let next_dir = self.direction.turn(self.turn);
                 self.turn = self.next_turn();
@@ -184,60 +316,47 @@ impl Direction {
             return *self;
         }
         match self {
-            Direction::North => {
-                match turn {
-                    Turn::Left => Direction::West,
-                    Turn::Right => Direction::East,
-                    _ => unreachable!(),
-                }
-            }
-            Direction::South => {
-                match turn {
-                    Turn::Left => Direction::East,
-                    Turn::Right => Direction::West,
-                    _ => unreachable!(),
-                }
-            }
-            Direction::West => {
-                match turn {
-                    Turn::Left => Direction::South,
-                    Turn::Right => Direction::North,
-                    _ => unreachable!(),
-                }
-            }
-            Direction::East => {
-                match turn {
-                    Turn::Left => Direction::North,
-                    Turn::Right => Direction::South,
-                    _ => unreachable!(),
-                }
-            }
+            Direction::North => match turn {
+                Turn::Left => Direction::West,
+                Turn::Right => Direction::East,
+                _ => unreachable!(),
+            },
+            Direction::South => match turn {
+                Turn::Left => Direction::East,
+                Turn::Right => Direction::West,
+                _ => unreachable!(),
+            },
+            Direction::West => match turn {
+                Turn::Left => Direction::South,
+                Turn::Right => Direction::North,
+                _ => unreachable!(),
+            },
+            Direction::East => match turn {
+                Turn::Left => Direction::North,
+                Turn::Right => Direction::South,
+                _ => unreachable!(),
+            },
         }
     }
 
     fn map_curve(&self, t: Track) -> Turn {
         match t {
-            Track::CurveLeft => {
-                match self {
-                    Direction::North | Direction::South => Turn::Left,
-                    _ => Turn::Right,
-                }
-            }
-            Track::CurveRight => {
-                match self {
-                    Direction::North | Direction::South => Turn::Right,
-                    _ => Turn::Left,
-
-                }
-            }
+            Track::CurveLeft => match self {
+                Direction::North | Direction::South => Turn::Left,
+                _ => Turn::Right,
+            },
+            Track::CurveRight => match self {
+                Direction::North | Direction::South => Turn::Right,
+                _ => Turn::Left,
+            },
             _ => Turn::Straight,
         }
     }
 }
 
-impl Into<char> for Direction {
-    fn into(self) -> char {
-        match self {
+impl From<Direction> for char {
+    fn from(direction: Direction) -> char {
+        match direction {
             Direction::North => '^',
             Direction::South => 'v',
             Direction::West => '<',
@@ -246,9 +365,9 @@ impl Into<char> for Direction {
     }
 }
 
-impl Into<char> for Track {
-    fn into(self) -> char {
-        match self {
+impl From<Track> for char {
+    fn from(track: Track) -> char {
+        match track {
             Track::CurveLeft => '\\',
             Track::CurveRight => '/',
             Track::Intersection => '+',
@@ -260,28 +379,88 @@ impl Into<char> for Track {
 
 #[cfg(test)]
 mod tests {
-    use std::{fs::File, io::Read};
     use super::*;
+
+    const LOOP: &str = r"/->-\
+|   |  /----\
+| /-+--+-\  |
+| | |  | v  |
+\-+-/  \-+--/
+  \------/
+";
+
+    const DEMOLITION: &str = r"/>-<\
+|   |
+| /<+-\
+| | | v
+\>+</ |
+  |   ^
+  \<->/
+";
+
     #[test]
     fn test() {
-        let mut f = File::open("./input/2018/day13_test.txt").unwrap();
-        let mut s = String::new();
-        f.read_to_string(&mut s).unwrap();
-        let input = generator(&s);
-        let cart = first_collision(&input);
-        assert_eq!(cart.x, 7);
-        assert_eq!(cart.y, 3);
+        let input = generator(LOOP).unwrap();
+        assert_eq!(first_collision(&input).unwrap(), "7,3");
+
+        let mut simulation = CartSimulation::new(&input);
+        for _ in 0..13 {
+            assert_eq!(simulation.tick().unwrap(), []);
+        }
+        let crash = simulation.tick().unwrap()[0].clone();
+        assert_eq!(
+            crash,
+            Crash {
+                tick: 14,
+                x: 7,
+                y: 3,
+                carts: (0, 1),
+            }
+        );
+        assert_eq!(
+            simulation.to_string(),
+            r"/---\
+|   |  /----\
+| /-+--+-\  |
+| | |  X |  |
+\-+-/  \-+--/
+  \------/
+"
+        );
     }
 
     #[test]
     fn test2() {
-        let mut f = File::open("./input/2018/day13_test2.txt").unwrap();
-        let mut s = String::new();
-        f.read_to_string(&mut s).unwrap();
-        let input = generator(&s);
-        let cart = last_cart(&input);
-        dbg!(&cart);
-        assert_eq!(cart.x, 6);
-        assert_eq!(cart.y, 4);
+        let input = generator(DEMOLITION).unwrap();
+        assert_eq!(last_cart(&input).unwrap(), "6,4");
+
+        let mut simulation = CartSimulation::new(&input);
+        let crashes = simulation.tick().unwrap().to_vec();
+        assert_eq!(crashes.len(), 3);
+        assert_eq!((crashes[0].x, crashes[0].y), (2, 0));
+        assert_eq!(simulation.carts().len(), 3);
+        assert!(simulation.to_string().starts_with("/-X-\\\n"));
+    }
+
+    #[test]
+    fn off_the_edge() {
+        for track in ["^\n|\n", "<-\n"] {
+            let input = generator(track).unwrap();
+            let error = CartSimulation::new(&input).tick().unwrap_err();
+            assert!(error.to_string().contains("flew off the track at (0, 0)"));
+        }
+    }
+
+    #[test]
+    fn no_collision() {
+        let single = generator("/>\\\n\\-/\n").unwrap();
+        assert!(first_collision(&single).is_err());
+        assert_eq!(last_cart(&single).unwrap(), "1,0");
+
+        let apart = generator("/>\\ /<\\\n\\-/ \\-/\n").unwrap();
+        for part in [first_collision, last_cart] {
+            let error = part(&apart).unwrap_err().to_string();
+            assert!(error.contains("round forever"), "{}", error);
+        }
     }
 }
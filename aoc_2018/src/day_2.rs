@@ -1,59 +1,51 @@
-use std::collections::HashMap;
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::word,
+    similarity::{self, Signature},
+};
+use nom::combinator::map;
+
+const DAY: Day = Day::new(2018, 2);
 
 #[aoc_generator(day2)]
-pub fn input_generator(input: &str) -> Vec<String> {
-    input.lines().map(|s| s.to_string()).collect()
+pub fn input_generator(input: &str) -> Result<Vec<String>, AocError> {
+    DAY.parse_lines(input, map(word, str::to_owned))
 }
 
 #[aoc(day2, part1)]
-pub fn one(input: &[String]) -> u64 {
-    let (twos, threes) = input.iter().fold((0, 0), |acc, x| {
-        let (two, three) = count(x);
-        (acc.0 + two as u64, acc.1 + three as u64)
-    });
-    twos * threes
+pub fn one(input: &[String]) -> Result<usize, AocError> {
+    let signatures: Vec<Signature> = input.iter().map(|id| Signature::new(id)).collect();
+    let with = |n| signatures.iter().filter(|s| s.has_count(n)).count();
+    Ok(with(2) * with(3))
 }
 
 #[aoc(day2, part2)]
-pub fn two(input: &[String]) -> String {
-    for i in 0..input.len() {
-        for j in i + 1..input.len() {
-            if let Some(c) = off_by_one(&input[i], &input[j]) {
-                let mut v = input[i].clone();
-                v.remove(c);
-                return v;
-            }
-        }
-    }
-    "Hello".to_owned()
+pub fn two(input: &[String]) -> Result<String, AocError> {
+    prototype_fabric(input)
+        .ok_or_else(|| DAY.error("no two box IDs differ by exactly one character"))
 }
 
-fn count(id: &str) -> (bool, bool) {
-    let mut map = HashMap::new();
-    id.chars().for_each(|c| *map.entry(c).or_insert(0) += 1);
-    map.values().fold((false, false), |acc, &x| {
-        ((x == 2 || acc.0), (x == 3 || acc.1))
-    })
+/// The letters shared by the first two box IDs that differ in exactly one
+/// position.
+fn prototype_fabric(ids: &[String]) -> Option<String> {
+    let &(i, j) = similarity::differ_by(ids, 1).first()?;
+    Some(similarity::common(&ids[i], &ids[j]))
 }
 
-fn off_by_one(a: &str, b: &str) -> Option<usize> {
-    let mut bad = false;
-    let mut diff_by_one = false;
-    let mut candidate = 0;
-    let chars = a.chars().zip(b.chars()).enumerate();
-    chars.for_each(|(i, (a, b))| {
-        if a != b {
-            if !diff_by_one {
-                diff_by_one = true;
-                candidate = i;
-            } else {
-                bad = true;
-            }
-        }
-    });
-    if diff_by_one && !bad {
-        Some(candidate)
-    } else {
-        None
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum() {
+        let ids = input_generator("abcdef\nbababc\nabbcde\nabcccd\naabcdd\nabcdee\nababab\n");
+        assert_eq!(one(&ids.unwrap()), Ok(12));
+    }
+
+    #[test]
+    fn prototype() {
+        let ids = input_generator("abcde\nfghij\nklmno\npqrst\nfguij\naxcye\nwvxyz\n").unwrap();
+        assert_eq!(two(&ids), Ok("fgij".to_owned()));
+        assert!(two(&ids[..3]).is_err());
     }
 }
@@ -1,160 +1,108 @@
-use lazy_static::lazy_static;
-use regex::Regex;
-use std::collections::HashMap;
+use aoc_utils::{
+    error::{AocError, Day},
+    grid::Grid,
+    image,
+    parse::{coordinates, labeled, unsigned, ws},
+    rect::{self, Overlaps, Rect},
+};
+use nom::{
+    character::complete::char,
+    combinator::map,
+    sequence::{preceded, separated_pair, tuple},
+};
+use std::io::{self, Write};
 
-#[aoc_generator(day3)]
-pub fn input_generator(input: &str) -> Vec<Rectangle> {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(r"-?\d+").unwrap();
-    }
+const DAY: Day = Day::new(2018, 3);
 
-    input
-        .lines()
-        .map(|string| {
-            let mut values = RE
-                .find_iter(string)
-                .map(|num| num.as_str().parse::<usize>().unwrap());
-            let id = values.next().unwrap();
-            let left = values.next().unwrap();
-            let top = values.next().unwrap();
-            let width = values.next().unwrap();
-            let height = values.next().unwrap();
-            Rectangle::new(id, left, top, width, height)
-        })
-        .collect()
+#[aoc_generator(day3)]
+pub fn input_generator(input: &str) -> Result<Vec<Claim>, AocError> {
+    let claim = tuple((
+        preceded(char('#'), unsigned),
+        labeled("@", coordinates),
+        labeled(":", separated_pair(ws(unsigned), char('x'), ws(unsigned))),
+    ));
+    DAY.parse_lines(
+        input,
+        map(claim, |(id, (left, top), (width, height))| Claim {
+            id,
+            rect: Rect::new(left, top, width, height),
+        }),
+    )
 }
 
 #[aoc(day3, part1)]
-pub fn one(inputs: &[Rectangle]) -> usize {
-    let mut points = HashMap::new();
-    for rec in inputs {
-        for point in rec.points() {
-            *points.entry(point).or_insert(0) += 1;
-        }
-    }
-    points.iter().filter(|(_, &v)| v >= 2).count()
+pub fn one(claims: &[Claim]) -> Result<usize, AocError> {
+    Ok(rect::covered_area(&rects(claims), 2))
 }
 
 #[aoc(day3, part2)]
-pub fn two(inputs: &[Rectangle]) -> usize {
-    let mut rectangles = inputs.to_vec();
-    for i in 0..rectangles.len() {
-        if !rectangles[i].overlaps {
-            for j in i + 1..rectangles.len() {
-                if rectangles[i].overlaps(&rectangles[j]) {
-                    rectangles.get_mut(i).unwrap().overlaps = true;
-                    rectangles.get_mut(j).unwrap().overlaps = true;
-                }
-            }
-        }
-    }
-    rectangles
-        .iter()
-        .filter(|r| !r.overlaps)
-        .map(|r| r.id)
+pub fn two(claims: &[Claim]) -> Result<usize, AocError> {
+    Overlaps::new(&rects(claims))
+        .isolated()
+        .map(|i| claims[i].id)
         .next()
-        .expect("Could not find non-overlapping rectangles.")
+        .ok_or_else(|| DAY.error("every claim overlaps another one"))
 }
 
-#[derive(Debug, Clone)]
-pub struct Rectangle {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Claim {
     id: usize,
-    top_l: Point,
-    top_r: Point,
-    bottom_l: Point,
-    bottom_r: Point,
-    overlaps: bool,
+    rect: Rect,
 }
 
-impl Rectangle {
-    fn new(id: usize, x: usize, y: usize, width: usize, height: usize) -> Self {
-        Rectangle {
-            id,
-            top_l: Point { x, y },
-            top_r: Point { x: x + width, y },
-            bottom_l: Point { x, y: y + height },
-            bottom_r: Point {
-                x: x + width,
-                y: y + height,
-            },
-            overlaps: false,
-        }
-    }
-
-    fn points(&self) -> Points {
-        let width = self.top_r.x - self.top_l.x;
-        let height = self.bottom_l.y - self.top_l.y;
-        Points::new(self.top_l, width, height)
-    }
-
-    fn overlaps(&self, r2: &Rectangle) -> bool {
-        self.top_l.x < r2.bottom_r.x
-            && self.bottom_r.x > r2.top_l.x
-            && self.top_l.y < r2.bottom_r.y
-            && self.bottom_r.y > r2.top_l.y
-    }
+fn rects(claims: &[Claim]) -> Vec<Rect> {
+    claims.iter().map(|c| c.rect).collect()
 }
 
-#[derive(Debug, Hash, Eq, PartialEq, Clone, Copy)]
-struct Point {
-    x: usize,
-    y: usize,
+/// The ids of the claims overlapping the claim with id `id`, or `None` if
+/// there is no such claim.
+pub fn overlapping(claims: &[Claim], id: usize) -> Option<Vec<usize>> {
+    let index = claims.iter().position(|c| c.id == id)?;
+    let overlaps = Overlaps::new(&rects(claims));
+    Some(overlaps.of(index).iter().map(|&i| claims[i].id).collect())
 }
 
-struct Points {
-    top_l: Point,
-    max_x: usize,
-    max_y: usize,
-    x: usize,
-    y: usize,
+/// How many claims cover each square inch of the fabric.
+pub fn heat_map(claims: &[Claim]) -> Grid<usize> {
+    rect::coverage(&rects(claims))
 }
 
-impl Points {
-    fn new(top_l: Point, width: usize, height: usize) -> Self {
-        Points {
-            top_l,
-            max_x: top_l.x + width - 1,
-            max_y: top_l.y + height - 1,
-            x: top_l.x,
-            y: top_l.y,
-        }
-    }
-}
-
-impl Iterator for Points {
-    type Item = Point;
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.y > self.max_y {
-            return None;
-        }
-
-        let point = Point {
-            x: self.x,
-            y: self.y,
-        };
-        self.x = {
-            if self.x == self.max_x {
-                self.y += 1;
-                self.top_l.x
-            } else {
-                self.x + 1
-            }
-        };
-        Some(point)
-    }
+/// Writes the heat map as a greyscale image, where unclaimed fabric is black
+/// and the most contested square inches are white.
+pub fn write_heat_map<W: Write>(claims: &[Claim], out: &mut W) -> io::Result<()> {
+    let heat = heat_map(claims);
+    let hottest = heat.iter().copied().max().unwrap_or(0).max(1);
+    image::write_pgm(out, &heat, |&n| (n * 255 / hottest) as u8)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    const EXAMPLE: &str = "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2\n";
+
     #[test]
-    fn it_works() {
-        let r = Rectangle::new(1, 1, 3, 4, 4);
-        let r2 = Rectangle::new(2, 3, 1, 4, 4);
-        let r3 = Rectangle::new(3, 5, 5, 2, 2);
-        assert!(r.overlaps(&r2));
-        assert!(r2.overlaps(&r));
-        assert!(!r2.overlaps(&r3));
-        assert!(!r.overlaps(&r3));
+    fn example() {
+        let claims = input_generator(EXAMPLE).unwrap();
+        assert_eq!(one(&claims), Ok(4));
+        assert_eq!(two(&claims), Ok(3));
+        assert_eq!(overlapping(&claims, 1), Some(vec![2]));
+        assert_eq!(overlapping(&claims, 3), Some(vec![]));
+        assert_eq!(overlapping(&claims, 4), None);
+    }
+
+    #[test]
+    fn heat() {
+        let claims = input_generator(EXAMPLE).unwrap();
+        let heat = heat_map(&claims);
+        assert_eq!((heat.width(), heat.height()), (7, 7));
+        assert_eq!(heat[(3, 3)], 2);
+        assert_eq!(heat[(0, 0)], 0);
+
+        let mut image = Vec::new();
+        write_heat_map(&claims, &mut image).unwrap();
+        assert_eq!(&image[..11], b"P5\n7 7\n255\n");
+        assert_eq!(image[11 + 3 * 7 + 3], 255);
+        assert_eq!(image[11 + 7 + 3], 127);
     }
 }
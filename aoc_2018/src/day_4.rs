@@ -1,137 +1,234 @@
-use chrono::{NaiveDateTime, Timelike};
-use lazy_static::lazy_static;
-use regex::Regex;
-use std::collections::HashMap;
-
-lazy_static! {
-    static ref RE_NUM: Regex = Regex::new(r"(?P<guard>\d+)").unwrap();
-    static ref RE: Regex = Regex::new(r"\[(.{16})\] (.*)$").unwrap();
-}
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::{labeled, timestamp, unsigned, ws, IResult, Timestamp},
+};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::map,
+    error::context,
+    sequence::{pair, terminated},
+};
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+const DAY: Day = Day::new(2018, 4);
+const MINUTES: usize = 60;
 
 #[aoc_generator(day4)]
-fn input_generator(input: &str) -> Vec<Event> {
-    struct Inner<'a> {
-        dt: NaiveDateTime,
-        s: &'a str,
-    }
-    let mut v: Vec<_> = input
-        .lines()
-        .map(|line| {
-            let caps = RE.captures(line).unwrap();
-            Inner {
-                dt: NaiveDateTime::parse_from_str(caps.get(1).unwrap().as_str(), "%Y-%m-%d %H:%M")
-                    .expect("Could not parse date"),
-                s: caps.get(2).unwrap().as_str(),
-            }
-        })
-        .collect();
-    v.sort_by(|a, b| a.dt.cmp(&b.dt));
-    let mut last_id = 0;
-    v.into_iter()
-        .map(|i| {
-            let action = Action::parse_str(i.s);
-            if let Action::BeginShift(id) = action {
-                last_id = id;
-            }
-            Event {
-                id: last_id,
-                dt: i.dt,
-                action,
-            }
-        })
-        .collect()
+fn input_generator(input: &str) -> Result<SleepLog, AocError> {
+    let events = DAY.parse_lines(input, pair(timestamp, ws(Action::parse)))?;
+    SleepLog::new(events)
 }
 
 #[aoc(day4, part1)]
-fn one(input: &[Event]) -> u64 {
-    let mut map = HashMap::new();
-    let mut curr_id = 0;
-    let mut sleep_time = 0;
-    for event in input {
-        match event.action {
-            Action::BeginShift(id) => {
-                curr_id = id;
+fn one(log: &SleepLog) -> Result<usize, AocError> {
+    let guard = log
+        .guards()
+        .max_by_key(|&guard| log.minutes_asleep(guard))
+        .ok_or_else(|| DAY.error("the log has no shifts"))?;
+    let (minute, _) = log
+        .sleepiest_minute(guard)
+        .ok_or_else(|| DAY.error("no guard ever falls asleep"))?;
+    Ok(guard * minute)
+}
+
+#[aoc(day4, part2)]
+fn two(log: &SleepLog) -> Result<usize, AocError> {
+    log.guards()
+        .filter_map(|guard| Some((guard, log.sleepiest_minute(guard)?)))
+        .max_by_key(|&(_, (_, times))| times)
+        .map(|(guard, (minute, _))| guard * minute)
+        .ok_or_else(|| DAY.error("no guard ever falls asleep"))
+}
+
+/// One guard's shift, with the minutes of the midnight hour they slept
+/// through.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shift {
+    pub guard: usize,
+    /// The month and day of the midnight hour the shift covers.
+    pub date: (u8, u8),
+    pub asleep: [bool; MINUTES],
+}
+
+/// Every shift in the log, in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SleepLog {
+    shifts: Vec<Shift>,
+}
+
+impl SleepLog {
+    /// Sorts the events and splits them into shifts, checking that each
+    /// shift only sleeps during the midnight hour of its own night, never
+    /// falls asleep twice or wakes up without sleeping, and ends awake.
+    fn new(mut events: Vec<(Timestamp, Action)>) -> Result<Self, AocError> {
+        events.sort_by_key(|&(time, _)| time);
+        let mut shifts: Vec<Shift> = Vec::new();
+        let mut fell_asleep = None;
+        for (time, action) in events {
+            let shift = match (action, shifts.last_mut()) {
+                (Action::BeginShift(guard), last) => {
+                    check_awake(last, fell_asleep)?;
+                    shifts.push(Shift {
+                        guard,
+                        date: midnight(time),
+                        asleep: [false; MINUTES],
+                    });
+                    continue;
+                }
+                (_, Some(shift)) => shift,
+                (_, None) => {
+                    return Err(DAY.error(format!("{} happens before the first shift", time)))
+                }
+            };
+            if time.hour != 0 {
+                return Err(DAY.error(format!(
+                    "{}: guard #{} changes state outside the midnight hour",
+                    time, shift.guard
+                )));
             }
-            Action::FallAsleep => {
-                sleep_time = u64::from(event.dt.minute());
+            if (time.month, time.day) != shift.date {
+                return Err(DAY.error(format!(
+                    "{}: guard #{} changes state after their shift's night",
+                    time, shift.guard
+                )));
             }
-            Action::WakeUp => {
-                let time_asleep = u64::from(event.dt.minute()) - sleep_time;
-                map.entry(curr_id).or_insert(Vec::new()).push(time_asleep);
+            let minute = usize::from(time.minute);
+            match (action, fell_asleep) {
+                (Action::FallAsleep, None) => fell_asleep = Some(minute),
+                (Action::WakeUp, Some(start)) if minute < start => {
+                    return Err(DAY.error(format!(
+                        "{}: guard #{} wakes up before falling asleep",
+                        time, shift.guard
+                    )))
+                }
+                (Action::WakeUp, Some(start)) => {
+                    shift.asleep[start..minute]
+                        .iter_mut()
+                        .for_each(|m| *m = true);
+                    fell_asleep = None;
+                }
+                (Action::FallAsleep, Some(_)) => {
+                    return Err(DAY.error(format!(
+                        "{}: guard #{} falls asleep while asleep",
+                        time, shift.guard
+                    )))
+                }
+                (_, _) => {
+                    return Err(DAY.error(format!(
+                        "{}: guard #{} wakes up without sleeping",
+                        time, shift.guard
+                    )))
+                }
             }
         }
+        check_awake(shifts.last_mut(), fell_asleep)?;
+        Ok(SleepLog { shifts })
     }
 
-    let max_id = map
-        .iter()
-        .map(|(k, v)| (k, v.iter().sum::<u64>()))
-        .max_by_key(|(_, y)| y.clone())
-        .unwrap()
-        .0;
-
-    let mut times = HashMap::new();
-    let mut t = 0;
-    input
-        .iter()
-        .filter(|x| x.id == *max_id)
-        .for_each(|x| match x.action {
-            Action::FallAsleep => t = x.dt.minute(),
-            Action::WakeUp => {
-                for i in t..x.dt.minute() {
-                    *times.entry(i).or_insert(0) += 1;
-                }
+    pub fn shifts(&self) -> &[Shift] {
+        &self.shifts
+    }
+
+    /// The shifts worked by `guard`.
+    pub fn shifts_of(&self, guard: usize) -> impl Iterator<Item = &Shift> + '_ {
+        self.shifts.iter().filter(move |s| s.guard == guard)
+    }
+
+    /// Every guard in the log, in increasing order.
+    pub fn guards(&self) -> impl Iterator<Item = usize> {
+        let guards: BTreeSet<usize> = self.shifts.iter().map(|s| s.guard).collect();
+        guards.into_iter()
+    }
+
+    /// How many of `guard`'s shifts they were asleep for, minute by minute.
+    pub fn histogram(&self, guard: usize) -> [usize; MINUTES] {
+        let mut histogram = [0; MINUTES];
+        for shift in self.shifts_of(guard) {
+            for (count, &asleep) in histogram.iter_mut().zip(shift.asleep.iter()) {
+                *count += usize::from(asleep);
             }
-            _ => {}
-        });
-    let max_time = times.iter().max_by_key(|(_, y)| y.clone()).unwrap().0;
+        }
+        histogram
+    }
 
-    *max_id as u64 * *max_time as u64
+    /// The total number of minutes `guard` slept.
+    pub fn minutes_asleep(&self, guard: usize) -> usize {
+        self.histogram(guard).iter().sum()
+    }
+
+    /// The minute `guard` was most often asleep, earliest first on ties, and
+    /// how many times, or `None` if they never slept.
+    pub fn sleepiest_minute(&self, guard: usize) -> Option<(usize, usize)> {
+        let histogram = self.histogram(guard);
+        let times = *histogram.iter().max()?;
+        let minute = histogram.iter().position(|&t| t == times)?;
+        (times > 0).then_some((minute, times))
+    }
 }
 
-#[aoc(day4, part2)]
-fn two(input: &[Event]) -> usize {
-    let mut mins: Vec<HashMap<usize, usize>> = vec![HashMap::new(); 60];
-    let mut t = 0;
-    input
-        .iter()
-        .filter(|x| x.action == Action::FallAsleep || x.action == Action::WakeUp)
-        .for_each(|event| match event.action {
-            Action::FallAsleep => t = event.dt.minute(),
-            Action::WakeUp => {
-                for min in t..event.dt.minute() {
-                    *mins
-                        .get_mut(min as usize)
-                        .unwrap()
-                        .entry(event.id)
-                        .or_insert(0) += 1;
-                }
-            }
-            _ => {}
-        });
-    let ans = mins
-        .iter()
-        .enumerate()
-        .filter_map(|(i, hashmap)| {
-            let v = hashmap.iter().max_by_key(|(_, &t)| t);
-            if let Some(tuple) = v {
-                Some((i, tuple))
-            } else {
-                None
-            }
-        })
-        .max_by_key(|(_, (_, &t))| t)
-        .expect("One");
-    ans.0 * (ans.1).0
+/// Fails if the guard of `shift` fell asleep and never woke up.
+fn check_awake(shift: Option<&mut Shift>, fell_asleep: Option<usize>) -> Result<(), AocError> {
+    match (shift, fell_asleep) {
+        (Some(shift), Some(minute)) => Err(DAY.error(format!(
+            "guard #{} falls asleep at 00:{:02} on {:02}-{:02} and never wakes up",
+            shift.guard, minute, shift.date.0, shift.date.1
+        ))),
+        _ => Ok(()),
+    }
 }
 
-#[derive(Debug)]
-pub struct Event {
-    id: usize,
-    dt: NaiveDateTime,
-    action: Action,
+/// The month and day of the first midnight at or after `time`.
+fn midnight(time: Timestamp) -> (u8, u8) {
+    if time.hour == 0 {
+        return (time.month, time.day);
+    }
+    let leap = time.year.is_multiple_of(4)
+        && (!time.year.is_multiple_of(100) || time.year.is_multiple_of(400));
+    let days = match time.month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    match (time.month, time.day) {
+        (12, 31) => (1, 1),
+        (month, day) if day >= days => (month + 1, 1),
+        (month, day) => (month, day + 1),
+    }
 }
 
-#[derive(Debug, PartialEq)]
+impl Display for SleepLog {
+    /// The chart from the puzzle text, one row per shift with `#` for the
+    /// minutes the guard slept.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let tens: String = (0..MINUTES)
+            .map(|m| (b'0' + (m / 10) as u8) as char)
+            .collect();
+        let ones: String = (0..MINUTES)
+            .map(|m| (b'0' + (m % 10) as u8) as char)
+            .collect();
+        writeln!(fmt, "Date   ID   Minute")?;
+        writeln!(fmt, "{:12}{}", "", tens)?;
+        writeln!(fmt, "{:12}{}", "", ones)?;
+        for shift in &self.shifts {
+            let id = format!("#{}", shift.guard);
+            let chart: String = shift
+                .asleep
+                .iter()
+                .map(|&asleep| if asleep { '#' } else { '.' })
+                .collect();
+            let (month, day) = shift.date;
+            writeln!(fmt, "{:02}-{:02}  {:<4} {}", month, day, id, chart)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Action {
     BeginShift(usize),
     FallAsleep,
@@ -139,16 +236,104 @@ enum Action {
 }
 
 impl Action {
-    fn parse_str(input: &str) -> Self {
-        match input {
-            "falls asleep" => Action::FallAsleep,
-            "wakes up" => Action::WakeUp,
-            _s => {
-                let id = RE_NUM.captures(_s).unwrap()["guard"]
-                    .parse::<usize>()
-                    .unwrap();
-                Action::BeginShift(id)
-            }
+    fn parse(input: &str) -> IResult<'_, Self> {
+        context(
+            "a guard action",
+            alt((
+                map(tag("falls asleep"), |_| Action::FallAsleep),
+                map(tag("wakes up"), |_| Action::WakeUp),
+                map(
+                    labeled("Guard #", terminated(unsigned, tag(" begins shift"))),
+                    Action::BeginShift,
+                ),
+            )),
+        )(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up
+[1518-11-04 00:02] Guard #99 begins shift
+[1518-11-04 00:36] falls asleep
+[1518-11-04 00:46] wakes up
+[1518-11-05 00:45] falls asleep
+[1518-11-05 00:03] Guard #99 begins shift
+[1518-11-05 00:55] wakes up
+";
+
+    #[test]
+    fn example() {
+        let log = input_generator(EXAMPLE).unwrap();
+        assert_eq!(log.guards().collect::<Vec<_>>(), [10, 99]);
+        assert_eq!(log.minutes_asleep(10), 50);
+        assert_eq!(log.sleepiest_minute(10), Some((24, 2)));
+        assert_eq!(log.histogram(99)[45], 3);
+        assert_eq!(log.shifts_of(99).count(), 3);
+        assert_eq!(one(&log), Ok(240));
+        assert_eq!(two(&log), Ok(4455));
+    }
+
+    #[test]
+    fn chart() {
+        let log = input_generator(EXAMPLE).unwrap();
+        let chart = log.to_string();
+        let lines: Vec<_> = chart.lines().collect();
+        assert_eq!(lines[0], "Date   ID   Minute");
+        assert_eq!(
+            lines[1],
+            "            000000000011111111112222222222333333333344444444445555555555"
+        );
+        assert_eq!(
+            lines[3],
+            "11-01  #10  .....####################.....#########################....."
+        );
+        assert_eq!(
+            lines[4],
+            "11-02  #99  ........................................##########.........."
+        );
+        assert_eq!(lines.len(), 8);
+    }
+
+    #[test]
+    fn malformed() {
+        let bad = [
+            "[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:05] wakes up",
+            "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:05] falls asleep\n\
+             [1518-11-01 00:07] falls asleep",
+            "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 23:00] Guard #10 begins shift\n[1518-11-01 23:05] falls asleep",
+            "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:30] falls asleep\n\
+             [1518-11-02 00:10] wakes up",
+            "[1518-10-31 23:58] Guard #10 begins shift\n[1518-11-02 00:05] falls asleep\n\
+             [1518-11-02 00:10] wakes up",
+        ];
+        for input in bad.iter() {
+            assert!(input_generator(input).is_err(), "{}", input);
         }
+        assert_eq!(
+            midnight(Timestamp {
+                year: 1518,
+                month: 2,
+                day: 28,
+                hour: 23,
+                minute: 58
+            }),
+            (3, 1)
+        );
     }
 }
@@ -1,62 +1,120 @@
-use std::cmp;
-const DIFF: u8 = b'a' - b'A';
+use aoc_utils::error::{AocError, Day};
+use rayon::prelude::*;
 
-#[inline]
-fn diff(c1: u8, c2: u8) -> bool {
-    cmp::max(c1, c2) - cmp::min(c1, c2) == DIFF
+const DAY: Day = Day::new(2018, 5);
+
+fn polymer(input: &str) -> Result<&[u8], AocError> {
+    let polymer = input.trim();
+    match polymer
+        .char_indices()
+        .find(|(_, c)| !c.is_ascii_alphabetic())
+    {
+        Some((i, c)) => Err(DAY.error(format!("unexpected unit `{}` at {}", c, i))),
+        None => Ok(polymer.as_bytes()),
+    }
 }
 
 #[aoc(day5, part1)]
-fn stack(input: &str) -> usize {
-    let mut stack = Vec::new();
-    for c in input.chars() {
-        if let Some(last) = stack.last() {
-            if diff(*last as u8, c as u8) {
-                stack.pop();
-            } else {
-                stack.push(c);
-            }
-        } else {
-            stack.push(c);
+fn one(input: &str) -> Result<usize, AocError> {
+    let polymer = polymer(input)?;
+    Ok(reduce(polymer, &Reactions::polarity(), |_| false).len())
+}
+
+#[aoc(day5, part2)]
+fn two(input: &str) -> Result<usize, AocError> {
+    let polymer = polymer(input)?;
+    let reactions = Reactions::polarity();
+    (b'a'..=b'z')
+        .into_par_iter()
+        .map(|unit| reduce(polymer, &reactions, |u| u.to_ascii_lowercase() == unit).len())
+        .min()
+        .ok_or_else(|| DAY.error("there are no unit types to remove"))
+}
+
+/// Which pairs of units destroy each other when they end up adjacent.
+#[derive(Clone)]
+pub struct Reactions {
+    table: Vec<bool>,
+}
+
+impl Reactions {
+    /// The puzzle's rule: a unit reacts with the same type of the opposite
+    /// polarity, i.e. the same letter in the other case.
+    pub fn polarity() -> Self {
+        let letters = (b'a'..=b'z').map(|c| (c, c.to_ascii_uppercase()));
+        Self::from_pairs(letters)
+    }
+
+    /// Each pair reacts in either order.
+    pub fn from_pairs<I: IntoIterator<Item = (u8, u8)>>(pairs: I) -> Self {
+        let mut table = vec![false; 256 * 256];
+        for (a, b) in pairs {
+            table[usize::from(a) * 256 + usize::from(b)] = true;
+            table[usize::from(b) * 256 + usize::from(a)] = true;
         }
+        Reactions { table }
+    }
+
+    #[inline]
+    pub fn react(&self, a: u8, b: u8) -> bool {
+        self.table[usize::from(a) * 256 + usize::from(b)]
     }
-    stack.len()
 }
 
-#[aoc(day5, part2)]
-fn two(input: &str) -> usize {
-    let letters: Vec<(char, char)> = (65..91)
-        .map(char::from)
-        .zip((97..123).map(char::from))
-        .collect();
-    let mut collapsed_lens = Vec::new();
-    for letter in letters {
-        let s = input.replace(letter.0, ""); // The two copies here
-        let s = s.replace(letter.1, ""); // and here are sad
-        collapsed_lens.push(stack(&s));
-    }
-
-    collapsed_lens.into_iter().min().unwrap_or(0)
+/// Fully reacts `polymer` in a single pass, leaving out every unit for which
+/// `skip` holds, and returns what is left.
+pub fn reduce<F>(polymer: &[u8], reactions: &Reactions, skip: F) -> Vec<u8>
+where
+    F: Fn(u8) -> bool,
+{
+    let mut stack = Vec::with_capacity(polymer.len());
+    for &unit in polymer.iter().filter(|&&u| !skip(u)) {
+        match stack.last() {
+            Some(&last) if reactions.react(last, unit) => {
+                stack.pop();
+            }
+            _ => stack.push(unit),
+        }
+    }
+    stack
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
     #[test]
     fn test() {
-        let s = "lDdhGghHHLAXxaKkFfDdsS";
-        assert!(diff('c' as u8, 'C' as u8));
-        assert!(diff('C' as u8, 'c' as u8));
-        assert!(!diff('A' as u8, 'c' as u8));
-        assert!(!diff('c' as u8, 'A' as u8));
-        assert!(!diff('A' as u8, 'A' as u8));
-        assert!(!diff('c' as u8, 'c' as u8));
-        stack(&s);
+        let reactions = Reactions::polarity();
+        assert!(reactions.react(b'c', b'C'));
+        assert!(reactions.react(b'C', b'c'));
+        assert!(!reactions.react(b'A', b'c'));
+        assert!(!reactions.react(b'c', b'A'));
+        assert!(!reactions.react(b'A', b'A'));
+        assert!(!reactions.react(b'c', b'c'));
+
+        let reduced = reduce(b"dabAcCaCBAcCcaDA", &reactions, |_| false);
+        assert_eq!(reduced, b"dabCBAcaDA");
+        let reduced = reduce(b"dabAcCaCBAcCcaDA", &reactions, |u| u == b'c' || u == b'C');
+        assert_eq!(reduced, b"daDA");
+        assert_eq!(one("dabAcCaCBAcCcaDA\n"), Ok(10));
+        assert_eq!(two("dabAcCaCBAcCcaDA\n"), Ok(4));
     }
 
     #[test]
     fn other() {
-        let s = "DdabAcCaCBxAcCcaACDAa";
-        assert_eq!(7, stack(&s));
+        let s = b"DdabAcCaCBxAcCcaACDAa";
+        assert_eq!(7, reduce(s, &Reactions::polarity(), |_| false).len());
+        assert!(one("abc1").is_err());
+        let error = one("abé").unwrap_err().to_string();
+        assert!(error.contains("unexpected unit `é` at 2"), "{}", error);
+    }
+
+    #[test]
+    fn custom_rules() {
+        let reactions = Reactions::from_pairs(vec![(b'x', b'y'), (b'(', b')')]);
+        assert_eq!(reduce(b"a(xy)b", &reactions, |_| false), b"ab");
+        assert_eq!(reduce(b"yx(aA)", &reactions, |_| false), b"(aA)");
+        assert_eq!(reduce(b"x(a)y", &reactions, |u| u == b'a'), b"");
     }
 }
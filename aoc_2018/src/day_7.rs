@@ -1,202 +1,97 @@
-use std::{
-    borrow::Borrow,
-    collections::{HashMap, HashSet},
-    hash::{Hash, Hasher},
-    str::FromStr,
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::{labeled, word, ws},
+    schedule::{schedule, Timeline},
+    search::topological_sort,
 };
+use nom::{
+    bytes::complete::tag,
+    combinator::map,
+    sequence::{pair, terminated},
+};
+use std::iter;
+
+const DAY: Day = Day::new(2018, 7);
+
+type Requirement = (String, String);
 
 #[aoc_generator(day7)]
-pub fn generator(input: &str) -> Vec<(char, char)> {
-    input
-        .lines()
-        .map(|l| {
-            let mut words = l.split_whitespace();
-            (
-                char::from_str(words.nth(1).unwrap()).unwrap(),
-                char::from_str(words.nth(5).unwrap()).unwrap(),
-            )
-        })
-        .collect()
+pub fn generator(input: &str) -> Result<Vec<Requirement>, AocError> {
+    let step = || map(word, str::to_owned);
+    let requirement = pair(
+        labeled("Step", ws(step())),
+        labeled("must be finished before step", ws(step())),
+    );
+    DAY.parse_lines(input, terminated(requirement, tag("can begin.")))
 }
 
 #[aoc(day7, part1)]
-pub fn order(requirements: &[(char, char)]) -> String {
-    let mut result = String::with_capacity(32);
-    let mut map = HashMap::with_capacity(32);
-    let mut set = HashSet::with_capacity(32);
-    for (r0, r1) in requirements {
-        set.insert(r0);
-        set.insert(r1);
-        map.entry(r1).or_insert(Vec::new()).push(*r0);
-    }
-    while !set.is_empty() {
-        for c in b'A'..=b'Z' {
-            let c = char::from(c);
-            if set.contains(&c)
-                && map
-                    .get(&c)
-                    .map_or(true, |req| req.iter().all(|x| !set.contains(x)))
-            {
-                result.push(c);
-                set.remove(&c);
-                break;
-            }
-        }
-    }
-    result
+pub fn order(requirements: &[Requirement]) -> Result<String, AocError> {
+    topological_sort(iter::empty(), requirements.iter().cloned())
+        .map(|order| order.concat())
+        .ok_or_else(|| DAY.error("the requirements contain a cycle"))
 }
 
 #[aoc(day7, part2)]
-pub fn part2(requirements: &[(char, char)]) -> usize {
-    solve(requirements, 5)
-}
-
-fn solve(requirements: &[(char, char)], workers: usize) -> usize {
-    let mut map = HashMap::with_capacity(32);
-    let mut set = HashSet::with_capacity(32);
-    for (r0, r1) in requirements {
-        set.insert(Step::new(*r0));
-        set.insert(Step::new(*r1));
-        map.entry(r1).or_insert(Vec::new()).push(*r0);
-    }
-
-    let mut i = 0;
-    let mut workers: Vec<Option<*mut Step>> = vec![None; workers];
-    while !set.is_empty() {
-        for worker in workers.iter_mut() {
-            if let Some(step) = worker {
-                let step: &mut Step = unsafe { &mut **step };
-                if step.done() {
-                    *worker = None;
-                    set.remove(step);
-                } else {
-                    step.decrement();
-                }
-            }
-            if worker.is_none() {
-                *worker = {
-                    let mut r = None;
-                    for c in b'A'..=b'Z' {
-                        let c = char::from(c);
-                        if set.contains(&c)
-                            && !set.get(&c).unwrap().in_progress()
-                            && map
-                                .get(&c)
-                                .map_or(true, |req| req.iter().all(|x| !set.contains(x)))
-                        {
-                            let s = set.get(&c).unwrap();
-                            let s: *mut Step = (s as *const Step) as *mut Step;
-                            unsafe {
-                                (*s).start();
-                            }
-                            r = Some(s);
-                            break;
-                        }
-                    }
-                    r
-                };
-            }
-        }
-        i += 1;
-    }
-    i - 1
-}
-
-struct Step {
-    name: char,
-    in_progress: bool,
-    time: u8,
-}
-
-impl Step {
-    fn new(name: char) -> Self {
-        Self {
-            name,
-            in_progress: false,
-            time: time(name) - 1,
-        }
-    }
-
-    fn decrement(&mut self) {
-        self.time -= 1;
-    }
-
-    fn in_progress(&self) -> bool {
-        self.in_progress
-    }
-
-    fn start(&mut self) {
-        self.in_progress = true;
-    }
-
-    fn done(&self) -> bool {
-        self.time == 0
-    }
+pub fn part2(requirements: &[Requirement]) -> Result<usize, AocError> {
+    Ok(solve(requirements, 5, 60)?.duration())
 }
 
-impl Hash for Step {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.name.hash(state);
-    }
+fn solve(
+    requirements: &[Requirement],
+    workers: usize,
+    base: usize,
+) -> Result<Timeline<String>, AocError> {
+    schedule(
+        iter::empty(),
+        requirements.iter().cloned(),
+        workers,
+        |step| time(step, base),
+    )
+    .map_err(|e| DAY.error(e))
 }
 
-impl Borrow<char> for Step {
-    fn borrow(&self) -> &char {
-        &self.name
-    }
-}
-
-impl PartialEq for Step {
-    fn eq(&self, other: &Self) -> bool {
-        self.name == other.name
-    }
-}
-impl Eq for Step {}
-
-fn time(c: char) -> u8 {
-    let mut b = [0; 1];
-    c.encode_utf8(&mut b);
-    b[0] - 4
+/// `base` seconds plus the alphabet position of every letter of the name.
+fn time(step: &str, base: usize) -> usize {
+    base + step
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| (c.to_ascii_uppercase() as u8 - b'A' + 1) as usize)
+        .sum::<usize>()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    const EXAMPLE: &str = "Step C must be finished before step A can begin.
+Step C must be finished before step F can begin.
+Step A must be finished before step B can begin.
+Step A must be finished before step D can begin.
+Step B must be finished before step E can begin.
+Step D must be finished before step E can begin.
+Step F must be finished before step E can begin.
+";
+
     #[test]
     fn part1() {
-        let v = vec![
-            ('C', 'A'),
-            ('C', 'F'),
-            ('A', 'B'),
-            ('A', 'D'),
-            ('B', 'E'),
-            ('D', 'E'),
-            ('F', 'E'),
-        ];
-
-        let s = order(&v);
-        assert_eq!(s, "CABDFE");
+        let v = generator(EXAMPLE).unwrap();
+        assert_eq!(order(&v).unwrap(), "CABDFE");
     }
 
     #[test]
     fn test_time() {
-        assert_eq!(61, time('A'));
-        assert_eq!(86, time('Z'));
+        assert_eq!(61, time("A", 60));
+        assert_eq!(86, time("Z", 60));
     }
 
     #[test]
     fn part2() {
-        let v = vec![
-            ('C', 'A'),
-            ('C', 'F'),
-            ('A', 'B'),
-            ('A', 'D'),
-            ('B', 'E'),
-            ('D', 'E'),
-            ('F', 'E'),
-        ];
-
-        let s = solve(&v, 2);
-        assert_eq!(s, 258);
+        let v = generator(EXAMPLE).unwrap();
+        let timeline = solve(&v, 2, 0).unwrap();
+        assert_eq!(timeline.duration(), 15);
+        assert_eq!(timeline.order().cloned().collect::<String>(), "CABFDE");
+        assert_eq!(solve(&v, 2, 60).unwrap().duration(), 258);
+        assert_eq!(solve(&v, 5, 60).unwrap().duration(), 253);
     }
 }
@@ -1,108 +1,300 @@
-fn build_tree(tree: &[usize]) -> (Node, usize) {
-    if tree[0] == 0 {
-        return (
-            Node {
-                children: Vec::new(),
-                metadata: tree[2..tree[1] + 2].iter().cloned().collect(),
-            },
-            tree[1] + 2,
-        );
-    }
-
-    let mut node = Node {
-        children: Vec::with_capacity(tree[0]),
-        metadata: Vec::with_capacity(tree[1]),
-    };
-
-    let mut consumed = 2;
-    for _ in 0..tree[0] {
-        let (child, n) = build_tree(&tree[consumed..]);
-        node.children.push(child);
-        consumed += n;
-    }
-
-    for _ in 0..tree[1] {
-        node.metadata.push(tree[consumed]);
-        consumed += 1;
-    }
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::integers,
+};
+use std::fmt::{self, Display};
 
-    (node, consumed)
-}
+const DAY: Day = Day::new(2018, 8);
 
 #[aoc_generator(day8)]
-pub fn generator(input: &str) -> Box<Node> {
-    let data: Vec<usize> = input
-        .split_whitespace()
-        .map(|x| x.parse().unwrap())
-        .collect();
-    let (tree, n) = build_tree(&data);
-    assert_eq!(n, data.len());
-    Box::new(tree)
+pub fn generator(input: &str) -> Result<Tree, AocError> {
+    let data: Vec<usize> = DAY.parse_with(input, integers)?;
+    Tree::parse(&data).map_err(|e| DAY.error(e))
 }
 
 #[aoc(day8, part1)]
-pub fn part1(tree: &Node) -> usize {
-    tree.metadata_sum()
+pub fn part1(tree: &Tree) -> Result<usize, AocError> {
+    Ok(tree.totals()[Tree::ROOT])
 }
 
 #[aoc(day8, part2)]
-pub fn part2(tree: &Node) -> usize {
-    tree.value()
+pub fn part2(tree: &Tree) -> Result<usize, AocError> {
+    Ok(tree.values()[Tree::ROOT])
 }
 
-#[derive(Debug)]
+/// A license tree, stored flat with every node before its children so deep
+/// trees never recurse.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tree {
+    nodes: Vec<Node>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Node {
-    children: Vec<Node>,
-    metadata: Vec<usize>,
+    /// The ids of the child nodes, in order.
+    pub children: Vec<usize>,
+    pub metadata: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeError {
+    /// The input ends at `position` in the middle of a node.
+    Truncated { position: usize },
+    /// `count` numbers follow the root node.
+    Trailing { count: usize },
+    /// The metadata entry at `position` refers to child 0 of a node that has
+    /// children.
+    ZeroIndex { position: usize },
 }
 
-impl Node {
-    fn metadata_sum(&self) -> usize {
-        if self.children.is_empty() {
-            self.metadata.iter().sum()
-        } else {
-            self.metadata.iter().sum::<usize>()
-                + self
-                    .children
-                    .iter()
-                    .map(|x| x.metadata_sum())
-                    .sum::<usize>()
+impl Tree {
+    pub const ROOT: usize = 0;
+
+    /// Reads a tree from its header format: a child count, a metadata count,
+    /// the children, then the metadata.
+    pub fn parse(data: &[usize]) -> Result<Self, TreeError> {
+        let mut position: usize = 0;
+        let mut take = |n: usize| {
+            let start = position;
+            let slice = position
+                .checked_add(n)
+                .and_then(|end| data.get(start..end))
+                .ok_or(TreeError::Truncated {
+                    position: data.len(),
+                })?;
+            position += n;
+            Ok((start, slice))
+        };
+
+        let mut nodes = vec![Node::default()];
+        // The nodes being read, with how many children they have left and
+        // how many metadata entries they end with.
+        let (_, header) = take(2)?;
+        let mut open = vec![(Tree::ROOT, header[0], header[1])];
+        while let Some((id, left, entries)) = open.last_mut() {
+            if *left > 0 {
+                *left -= 1;
+                let child = nodes.len();
+                nodes[*id].children.push(child);
+                nodes.push(Node::default());
+                let (_, header) = take(2)?;
+                open.push((child, header[0], header[1]));
+            } else {
+                let (start, metadata) = take(*entries)?;
+                let node = &mut nodes[*id];
+                if !node.children.is_empty() {
+                    if let Some(i) = metadata.iter().position(|&entry| entry == 0) {
+                        return Err(TreeError::ZeroIndex {
+                            position: start + i,
+                        });
+                    }
+                }
+                node.metadata = metadata.to_vec();
+                open.pop();
+            }
+        }
+        match data.len() - position {
+            0 => Ok(Tree { nodes }),
+            count => Err(TreeError::Trailing { count }),
+        }
+    }
+
+    /// The tree back in its header format.
+    pub fn serialize(&self) -> Vec<usize> {
+        let mut data = Vec::new();
+        let mut open = vec![(Tree::ROOT, 0)];
+        let root = &self.nodes[Tree::ROOT];
+        data.extend([root.children.len(), root.metadata.len()]);
+        while let Some((id, next)) = open.last_mut() {
+            let node = &self.nodes[*id];
+            match node.children.get(*next) {
+                Some(&child) => {
+                    *next += 1;
+                    let child_node = &self.nodes[child];
+                    data.extend([child_node.children.len(), child_node.metadata.len()]);
+                    open.push((child, 0));
+                }
+                None => {
+                    data.extend(&node.metadata);
+                    open.pop();
+                }
+            }
         }
+        data
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
     }
 
-    fn value(&self) -> usize {
-        if self.children.is_empty() {
-            self.metadata_sum()
-        } else {
-            self.metadata
+    pub fn node(&self, id: usize) -> &Node {
+        &self.nodes[id]
+    }
+
+    /// The node reached by following `path` from the root, where each step
+    /// is a 0-based child position.
+    pub fn at(&self, path: &[usize]) -> Option<usize> {
+        path.iter().try_fold(Tree::ROOT, |id, &step| {
+            self.nodes[id].children.get(step).copied()
+        })
+    }
+
+    /// The metadata sum of every node's subtree, by node id.
+    pub fn totals(&self) -> Vec<usize> {
+        self.bottom_up(|node, totals| {
+            let own: usize = node.metadata.iter().sum();
+            own + node.children.iter().map(|&c| totals[c]).sum::<usize>()
+        })
+    }
+
+    /// The value of every node, by node id: a leaf is worth its metadata sum,
+    /// any other node the values of the children its metadata points at.
+    pub fn values(&self) -> Vec<usize> {
+        self.bottom_up(|node, values| {
+            if node.children.is_empty() {
+                return node.metadata.iter().sum();
+            }
+            node.metadata
                 .iter()
-                .map(|entry| {
-                    self.children
-                        .get(*entry - 1)
-                        .map_or(0, |child| child.value())
-                })
+                .filter_map(|entry| node.children.get(entry.checked_sub(1)?))
+                .map(|&child| values[child])
                 .sum()
+        })
+    }
+
+    /// Computes a number for every node from its children's numbers, going
+    /// through the nodes last to first so children always come first.
+    fn bottom_up<F>(&self, f: F) -> Vec<usize>
+    where
+        F: Fn(&Node, &[usize]) -> usize,
+    {
+        let mut results = vec![0; self.nodes.len()];
+        for id in (0..self.nodes.len()).rev() {
+            results[id] = f(&self.nodes[id], &results);
+        }
+        results
+    }
+}
+
+impl Display for Tree {
+    /// One line per node, indented by depth, with its path, metadata,
+    /// subtree metadata sum and value.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let (totals, values) = (self.totals(), self.values());
+        let mut open = vec![(Tree::ROOT, Vec::new())];
+        while let Some((id, path)) = open.pop() {
+            let node = &self.nodes[id];
+            let metadata: Vec<String> = node.metadata.iter().map(usize::to_string).collect();
+            writeln!(
+                fmt,
+                "{}{:?}  metadata: {}  total: {}  value: {}",
+                "  ".repeat(path.len()),
+                path,
+                metadata.join(" "),
+                totals[id],
+                values[id]
+            )?;
+            for (i, &child) in node.children.iter().enumerate().rev() {
+                let mut child_path = path.clone();
+                child_path.push(i);
+                open.push((child, child_path));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for TreeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TreeError::Truncated { position } => {
+                write!(fmt, "the input ends mid-node after {} numbers", position)
+            }
+            TreeError::Trailing { count } => {
+                write!(fmt, "{} trailing numbers after the root node", count)
+            }
+            TreeError::ZeroIndex { position } => {
+                write!(fmt, "number {} refers to child 0", position + 1)
+            }
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    #[test]
-    fn d8p1() {
-        let v = input();
-        let (tree, _) = super::build_tree(&v);
-        assert_eq!(138, super::part1(&tree));
-    }
+    use super::*;
 
     fn input() -> Vec<usize> {
         vec![2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2]
     }
 
+    #[test]
+    fn d8p1() {
+        let tree = Tree::parse(&input()).unwrap();
+        assert_eq!(138, part1(&tree).unwrap());
+    }
+
     #[test]
     fn d8p2() {
-        let v = input();
-        let (tree, _) = super::build_tree(&v);
-        assert_eq!(66, super::part2(&tree));
+        let tree = Tree::parse(&input()).unwrap();
+        assert_eq!(66, part2(&tree).unwrap());
+    }
+
+    #[test]
+    fn queries() {
+        let tree = generator("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2\n").unwrap();
+        assert_eq!(tree.serialize(), input());
+        assert_eq!(tree.len(), 4);
+        let d = tree.at(&[1, 0]).unwrap();
+        assert_eq!(tree.node(d).metadata, [99]);
+        assert_eq!(tree.at(&[0, 0]), None);
+        assert_eq!(tree.values()[tree.at(&[1]).unwrap()], 0);
+        assert_eq!(
+            tree.to_string(),
+            "[]  metadata: 1 1 2  total: 138  value: 66\n\
+             \x20 [0]  metadata: 10 11 12  total: 33  value: 33\n\
+             \x20 [1]  metadata: 2  total: 101  value: 0\n\
+             \x20   [1, 0]  metadata: 99  total: 99  value: 99\n"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Tree::parse(&[1]), Err(TreeError::Truncated { position: 1 }));
+        assert_eq!(
+            Tree::parse(&[1, 1, 0, 1, 5]),
+            Err(TreeError::Truncated { position: 5 })
+        );
+        assert_eq!(
+            Tree::parse(&[0, 1, 5, 7, 7]),
+            Err(TreeError::Trailing { count: 2 })
+        );
+        assert_eq!(
+            Tree::parse(&[1, 2, 0, 1, 0, 1, 0]),
+            Err(TreeError::ZeroIndex { position: 6 })
+        );
+        assert!(Tree::parse(&[1, 1, 0, 1, 0, 1]).is_ok());
+        assert_eq!(
+            Tree::parse(&[0, usize::MAX]),
+            Err(TreeError::Truncated { position: 2 })
+        );
+    }
+
+    #[test]
+    fn deep() {
+        let depth = 200_000;
+        let mut data = vec![1, 1];
+        data = data.repeat(depth);
+        data.extend([0, 1, 1]);
+        data.extend(vec![1; depth]);
+        let tree = Tree::parse(&data).unwrap();
+        assert_eq!(tree.len(), depth + 1);
+        assert_eq!(tree.values()[Tree::ROOT], 1);
+        assert_eq!(tree.totals()[Tree::ROOT], depth + 1);
+        assert_eq!(tree.serialize(), data);
     }
 }
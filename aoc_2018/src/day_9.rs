@@ -1,84 +1,187 @@
-use linked_list::{Cursor, LinkedList};
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::{labeled, unsigned, ws},
+    ring::Ring,
+};
+use nom::{
+    bytes::complete::tag,
+    sequence::{pair, terminated},
+};
+
+const DAY: Day = Day::new(2018, 9);
 
 #[aoc_generator(day9)]
-fn generator(input: &str) -> Box<(usize, usize)> {
-    let mut input = input.split_whitespace();
-    Box::new((
-        input.next().unwrap().parse().unwrap(),
-        input.nth(5).unwrap().parse().unwrap(),
-    ))
+fn generator(input: &str) -> Result<Box<(usize, usize)>, AocError> {
+    let game = pair(
+        unsigned,
+        labeled("players; last marble is worth", ws(unsigned)),
+    );
+    DAY.parse_with(input, ws(terminated(game, tag("points"))))
+        .map(Box::new)
 }
 
 #[aoc(day9, part1)]
-fn part1(input: &(usize, usize)) -> usize {
-    let (players, num_marbles) = *input;
-    let mut list = LinkedList::new();
-    let mut points = vec![0; players];
-    let mut marbles = MarbleRing::new(&mut list);
-    for marble in 0..=num_marbles {
-        if marble != 0 && marble % 23 == 0 {
-            points[marble % players] += marble;
-            points[marble % players] += marbles.remove_marble()
-        } else {
-            marbles.add_marble(marble);
-        }
-    }
-    *points.iter().max().unwrap()
+fn part1(input: &(usize, usize)) -> Result<usize, AocError> {
+    let (players, last_marble) = *input;
+    let scores = Rules::default().play(players, last_marble)?;
+    Ok(scores.high_score())
 }
 
 #[aoc(day9, part2)]
-fn part2(input: &(usize, usize)) -> usize {
+fn part2(input: &(usize, usize)) -> Result<usize, AocError> {
     part1(&(input.0, input.1 * 100))
 }
 
-struct MarbleRing<'a> {
-    len: usize,
-    cursor: Cursor<'a, usize>,
+/// How a marble game is played. A marble numbered a multiple of
+/// `special_multiple` is kept instead of placed, and its player also takes
+/// the marble `removal_offset` counter-clockwise of the current one. Any
+/// other marble goes `placement_offset` marbles clockwise of the current one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    pub special_multiple: usize,
+    pub removal_offset: usize,
+    pub placement_offset: usize,
+}
+
+/// What a player scored on one turn: the marble they kept and the marble
+/// they took from the circle.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Scoring {
+    pub marble: usize,
+    pub removed: usize,
+}
+
+#[derive(Debug)]
+pub struct Scores {
+    history: Vec<Vec<Scoring>>,
 }
 
-impl<'a> MarbleRing<'a> {
-    fn new(list: &'a mut LinkedList<usize>) -> Self {
-        Self {
-            len: 0,
-            cursor: list.cursor(),
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            special_multiple: 23,
+            removal_offset: 7,
+            placement_offset: 1,
         }
     }
+}
 
-    fn add_marble(&mut self, marble: usize) {
-        if self.len == 0 {
-            self.cursor.insert(marble);
-        } else {
-            let mut a = false;
-            for _ in 0..2 {
-                a |= self.cursor.next().is_none();
-            }
-            if a {
-                self.cursor.next();
-            }
-
-            self.cursor.insert(marble);
+impl Rules {
+    pub fn new(special_multiple: usize, removal_offset: usize, placement_offset: usize) -> Self {
+        Rules {
+            special_multiple,
+            removal_offset,
+            placement_offset,
         }
-        self.len += 1;
     }
 
-    fn remove_marble(&mut self) -> usize {
-        let mut a = false;
-        for _ in 0..7 {
-            a |= self.cursor.prev().is_none();
+    pub fn play(&self, players: usize, last_marble: usize) -> Result<Scores, AocError> {
+        if players == 0 {
+            return Err(DAY.error("the game needs at least one player"));
+        }
+        if self.special_multiple == 0 {
+            return Err(DAY.error("the special multiple must be positive"));
         }
-        if a {
-            self.cursor.prev();
+        let mut circle = Ring::with_capacity(last_marble + 1);
+        let mut history = vec![Vec::new(); players];
+        circle.insert(0);
+        for marble in 1..=last_marble {
+            if marble % self.special_multiple == 0 {
+                circle.rotate_left(self.removal_offset);
+                let removed = circle.remove().unwrap_or(0);
+                history[(marble - 1) % players].push(Scoring { marble, removed });
+            } else {
+                circle.rotate_right(self.placement_offset);
+                circle.insert(marble);
+            }
         }
-        self.len -= 1;
-        self.cursor.remove().unwrap()
+        Ok(Scores { history })
+    }
+}
+
+impl Scores {
+    /// What each player scored, turn by turn, counting players from 0.
+    pub fn history(&self) -> &[Vec<Scoring>] {
+        &self.history
+    }
+
+    /// Player `player`'s total, counting players from 0.
+    pub fn total(&self, player: usize) -> usize {
+        self.history[player]
+            .iter()
+            .map(|s| s.marble + s.removed)
+            .sum()
+    }
+
+    pub fn high_score(&self) -> usize {
+        (0..self.history.len())
+            .map(|player| self.total(player))
+            .max()
+            .unwrap_or(0)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn d9p1() {
-        assert_eq!(32, super::part1(&(9, 25)));
-        assert_eq!(8317, super::part1(&(10, 1618)));
+        assert_eq!(32, part1(&(9, 25)).unwrap());
+        assert_eq!(8317, part1(&(10, 1618)).unwrap());
+        assert_eq!(146373, part1(&(13, 7999)).unwrap());
+        assert_eq!(37305, part1(&(30, 5807)).unwrap());
+    }
+
+    #[test]
+    fn history() {
+        let scores = Rules::default().play(9, 25).unwrap();
+        let scoring = Scoring {
+            marble: 23,
+            removed: 9,
+        };
+        assert_eq!(scores.history[4], [scoring]);
+        assert_eq!(scores.total(4), 32);
+        assert!(Rules::default().play(0, 25).is_err());
+    }
+
+    /// Plays the game on a plain `Vec`, moving every marble on each insert.
+    fn replay(rules: Rules, players: usize, last_marble: usize) -> usize {
+        let (mut circle, mut current) = (vec![0], 0);
+        let mut totals = vec![0; players];
+        for marble in 1..=last_marble {
+            let len = circle.len();
+            if marble % rules.special_multiple == 0 {
+                current = (current + len - rules.removal_offset % len) % len;
+                totals[(marble - 1) % players] += marble + circle.remove(current);
+                current %= circle.len();
+            } else {
+                current = (current + rules.placement_offset) % len + 1;
+                circle.insert(current, marble);
+            }
+        }
+        totals.into_iter().max().unwrap()
+    }
+
+    #[test]
+    fn custom_rules() {
+        let scores = Rules::new(3, 1, 1).play(2, 6).unwrap();
+        assert_eq!((scores.total(0), scores.total(1)), (3, 8));
+        assert_eq!(
+            scores.history()[1],
+            [Scoring {
+                marble: 6,
+                removed: 2
+            }]
+        );
+        for &rules in &[
+            Rules::new(5, 2, 1),
+            Rules::new(23, 7, 3),
+            Rules::new(2, 9, 4),
+        ] {
+            let high_score = rules.play(7, 500).unwrap().high_score();
+            assert_eq!(high_score, replay(rules, 7, 500), "{:?}", rules);
+        }
+        assert_eq!(Rules::new(23, 7, 1), Rules::default());
     }
 }
@@ -32,33 +32,45 @@ The fuel required by a module of mass 100756 and its fuel is: 33583 + 11192 + 37
 What is the sum of the fuel requirements for all of the modules on your spacecraft when also taking into account the mass of the added fuel? (Calculate the fuel requirements for each module separately, then add them all up at the end.)
 */
 
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::unsigned,
+};
+
+const DAY: Day = Day::new(2019, 1);
+
 #[aoc_generator(day1)]
-pub fn generator(input: &str) -> Vec<usize> {
-    input.lines().map(|s| s.parse::<usize>().unwrap()).collect()
+pub fn generator(input: &str) -> Result<Vec<usize>, AocError> {
+    DAY.parse_lines(input, unsigned)
 }
 
 #[aoc(day1, part1)]
-pub fn part1(module_masses: &[usize]) -> usize {
-    module_masses.iter().map(|mass| (mass / 3) - 2).sum()
+pub fn part1(module_masses: &[usize]) -> Result<usize, AocError> {
+    Ok(module_masses.iter().map(|&mass| fuel(mass)).sum())
 }
 
 #[aoc(day1, part2)]
-pub fn part2(module_masses: &[usize]) -> usize {
-    module_masses
+pub fn part2(module_masses: &[usize]) -> Result<usize, AocError> {
+    Ok(module_masses
         .iter()
         .map(|mass| {
-            let fuel_mass = (mass / 3) - 2;
+            let fuel_mass = fuel(*mass);
             fuel_for_fuel(fuel_mass, fuel_mass)
         })
-        .sum()
+        .sum())
+}
+
+/// The fuel a mass needs, which is never negative.
+pub fn fuel(mass: usize) -> usize {
+    (mass / 3).saturating_sub(2)
 }
 
 pub fn fuel_for_fuel(fuel_mass: usize, total_fuel: usize) -> usize {
-    let needed_fuel: isize = (fuel_mass as isize / 3) - 2;
-    if needed_fuel <= 0 {
+    let needed_fuel = fuel(fuel_mass);
+    if needed_fuel == 0 {
         total_fuel
     } else {
-        fuel_for_fuel(needed_fuel as usize, total_fuel + needed_fuel as usize)
+        fuel_for_fuel(needed_fuel, total_fuel + needed_fuel)
     }
 }
 
@@ -69,15 +81,22 @@ mod tests {
 
     #[test]
     fn d01_p1() {
-        assert_eq!(part1(&DATA[0..1]), 2);
-        assert_eq!(part1(&DATA[1..2]), 654);
-        assert_eq!(part1(&DATA[2..3]), 33583);
+        assert_eq!(part1(&DATA[0..1]), Ok(2));
+        assert_eq!(part1(&DATA[1..2]), Ok(654));
+        assert_eq!(part1(&DATA[2..3]), Ok(33583));
     }
 
     #[test]
     fn d01_p2() {
-        assert_eq!(part2(&DATA[0..1]), 2);
-        assert_eq!(part2(&DATA[1..2]), 966);
-        assert_eq!(part2(&DATA[2..3]), 50346);
+        assert_eq!(part2(&DATA[0..1]), Ok(2));
+        assert_eq!(part2(&DATA[1..2]), Ok(966));
+        assert_eq!(part2(&DATA[2..3]), Ok(50346));
+    }
+
+    #[test]
+    fn small_masses() {
+        let masses = generator("0\n5\n8\n").unwrap();
+        assert_eq!(part1(&masses), Ok(0));
+        assert_eq!(part2(&masses), Ok(0));
     }
 }
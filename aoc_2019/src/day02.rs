@@ -1,39 +1,45 @@
 use crate::intcode::IntcodeComputer;
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::integer_list,
+};
+
+const DAY: Day = Day::new(2019, 2);
 
 #[aoc_generator(day2)]
-fn generator(input: &str) -> Vec<isize> {
-    let mut input: Vec<isize> = input
-        .split(',')
-        .map(|v| v.parse::<isize>().unwrap())
-        .collect();
+fn generator(input: &str) -> Result<Vec<isize>, AocError> {
+    let mut input: Vec<isize> = DAY.parse_with(input, integer_list(","))?;
+    if input.len() < 3 {
+        return Err(DAY.error("the program is too short to hold a noun and a verb"));
+    }
     input[1] = 12;
     input[2] = 2;
-    input
+    Ok(input)
 }
 
 #[aoc(day2, part1)]
-fn part1(input: &[isize]) -> isize {
+fn part1(input: &[isize]) -> Result<isize, AocError> {
     let in_iter = [];
     let out_fn = |_| {};
     let mut comp = IntcodeComputer::new(input, &in_iter, out_fn);
-    comp.run().unwrap();
+    comp.run().map_err(|e| DAY.error(e))?;
     let m = comp.memory();
-    m[0]
+    Ok(m[0])
 }
 
 #[aoc(day2, part2)]
-fn part2(input: &[isize]) -> isize {
+fn part2(input: &[isize]) -> Result<isize, AocError> {
     let mut input = Vec::from(input);
     for noun in 0..=99 {
         for verb in 0..=99 {
             input[1] = noun;
             input[2] = verb;
-            if part1(&input) == 19_690_720 {
-                return 100 * noun + verb;
+            if part1(&input) == Ok(19_690_720) {
+                return Ok(100 * noun + verb);
             }
         }
     }
-    0
+    Err(DAY.error("no noun and verb produce 19690720"))
 }
 
 #[cfg(test)]
@@ -42,6 +48,12 @@ mod test {
     #[test]
     fn d02_p1() {
         let in1 = [1, 1, 1, 4, 99, 5, 6, 0, 99];
-        assert_eq!(part1(&in1), 30);
+        assert_eq!(part1(&in1), Ok(30));
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(part1(&[301, 0, 0, 0, 99]).is_err());
+        assert!(part2(&[1, 0, 0, 0, 99]).is_err());
     }
 }
@@ -1,99 +1,247 @@
-use crate::utils::HashMapExtension;
-use std::{collections::HashMap, convert::TryInto};
+use crate::utils::{Direction, Point};
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::{symbol, unsigned},
+};
+use nom::{character::complete::char, combinator::map, multi::separated_list1, sequence::pair};
+use std::{collections::HashSet, fmt::Write};
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
-struct Point {
-    x: isize,
-    y: isize,
+const DAY: Day = Day::new(2019, 3);
+const ORIGIN: Point = Point { x: 0, y: 0 };
+
+#[aoc_generator(day3)]
+fn generator(input: &str) -> Result<Vec<Wire>, AocError> {
+    let direction = symbol(&[
+        ('R', Direction::East),
+        ('D', Direction::South),
+        ('U', Direction::North),
+        ('L', Direction::West),
+    ]);
+    let path = separated_list1(char(','), pair(direction, unsigned::<isize>));
+    DAY.parse_lines(input, map(path, |moves| Wire::new(&moves)))
 }
 
-#[derive(Copy, Clone)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
+#[aoc(day3, part1)]
+fn part1(wires: &[Wire]) -> Result<usize, AocError> {
+    closest(wires, Crossing::manhattan)
 }
 
-#[aoc_generator(day3)]
-fn generator(input: &str) -> Vec<HashMap<Point, usize>> {
-    let mut cables = Vec::new();
-    input.lines().enumerate().for_each(|(cable, line)| {
-        let mut cursor = Point { x: 0, y: 0 };
-        let mut steps = 0;
-        cables.push(HashMap::new());
-        line.split(',')
-            .map(|v| {
-                (
-                    Direction::from(v.chars().nth(0).unwrap()),
-                    v[1..].parse::<usize>().unwrap(),
-                )
-            })
-            .for_each(|(dir, value)| {
-                for _ in 0..value {
-                    cursor.step(dir);
-                    steps += 1;
-                    let map = cables.get_mut(cable).unwrap();
-                    if let Some(old) = map.insert(cursor, steps) {
-                        map.insert(cursor, old);
-                    }
-                }
-            });
-    });
-    cables
+#[aoc(day3, part2)]
+fn part2(wires: &[Wire]) -> Result<usize, AocError> {
+    closest(wires, Crossing::total_steps)
 }
 
-fn solve<F>(input: &[HashMap<Point, usize>], func: F) -> usize
+fn closest<F>(wires: &[Wire], distance: F) -> Result<usize, AocError>
 where
-    F: FnMut(&Point) -> usize,
+    F: Fn(&Crossing) -> usize,
 {
-    input[0]
-        .keys()
-        .intersection(&input[1])
-        .map(func)
+    crossings(wires)
+        .iter()
+        .map(distance)
         .min()
-        .unwrap()
+        .ok_or_else(|| DAY.error("the wires never cross"))
 }
 
-#[aoc(day3, part1)]
-fn part1(input: &[HashMap<Point, usize>]) -> usize {
-    solve(input, |p| p.manhattan())
+/// A straight, horizontal or vertical piece of wire.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Segment {
+    start: Point,
+    end: Point,
+    /// How far along the wire `start` is.
+    steps: usize,
 }
 
-#[aoc(day3, part2)]
-fn part2(input: &[HashMap<Point, usize>]) -> usize {
-    solve(input, |point| {
-        input[0].get(point).unwrap() + input[1].get(point).unwrap()
-    })
+impl Segment {
+    fn min(&self) -> Point {
+        Point {
+            x: self.start.x.min(self.end.x),
+            y: self.start.y.min(self.end.y),
+        }
+    }
+
+    fn max(&self) -> Point {
+        Point {
+            x: self.start.x.max(self.end.x),
+            y: self.start.y.max(self.end.y),
+        }
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        let (min, max) = (self.min(), self.max());
+        (min.x..=max.x).contains(&point.x) && (min.y..=max.y).contains(&point.y)
+    }
+
+    /// Every point the two segments share: none, one, or a run of them when
+    /// they lie along the same line.
+    fn intersections(&self, other: &Segment) -> impl Iterator<Item = Point> {
+        let (a, b) = (self.min(), self.max());
+        let (c, d) = (other.min(), other.max());
+        let (x0, x1) = (a.x.max(c.x), b.x.min(d.x));
+        let (y0, y1) = (a.y.max(c.y), b.y.min(d.y));
+        (y0..=y1).flat_map(move |y| (x0..=x1).map(move |x| Point { x, y }))
+    }
 }
 
-impl Point {
-    #[inline]
-    fn step(&mut self, dir: Direction) {
-        match dir {
-            Direction::East => self.x += 1,
-            Direction::West => self.x -= 1,
-            Direction::North => self.y += 1,
-            Direction::South => self.y -= 1,
+/// A wire laid out from the origin, as the segments it runs along.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Wire {
+    segments: Vec<Segment>,
+}
+
+impl Wire {
+    fn new(moves: &[(Direction, isize)]) -> Self {
+        let mut segments = Vec::with_capacity(moves.len());
+        let (mut start, mut steps) = (ORIGIN, 0);
+        for &(direction, length) in moves {
+            let mut end = start;
+            match direction {
+                Direction::East => end.x += length,
+                Direction::West => end.x -= length,
+                Direction::North => end.y += length,
+                Direction::South => end.y -= length,
+            }
+            segments.push(Segment { start, end, steps });
+            start = end;
+            steps += length as usize;
         }
+        Wire { segments }
     }
 
-    #[inline]
-    fn manhattan(&self) -> usize {
-        (self.x.abs() + self.y.abs()).try_into().unwrap()
+    /// How far along the wire it first reaches `point`.
+    fn steps_to(&self, point: Point) -> Option<usize> {
+        self.segments
+            .iter()
+            .find(|s| s.contains(point))
+            .map(|s| s.steps + manhattan(s.start, point))
     }
 }
 
-impl From<char> for Direction {
-    fn from(input: char) -> Self {
-        match input {
-            'R' => Self::East,
-            'D' => Self::South,
-            'U' => Self::North,
-            'L' => Self::West,
-            _ => unreachable!(),
+/// A point other than the origin where two different wires meet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Crossing {
+    pub point: Point,
+    /// The indices of the two wires.
+    pub wires: (usize, usize),
+    /// How far along each wire the crossing is.
+    pub steps: (usize, usize),
+}
+
+impl Crossing {
+    pub fn manhattan(&self) -> usize {
+        manhattan(ORIGIN, self.point)
+    }
+
+    pub fn total_steps(&self) -> usize {
+        self.steps.0 + self.steps.1
+    }
+}
+
+/// Every crossing between every pair of wires, found by testing their
+/// segments pairwise. Each point is reported once per pair of wires, in the
+/// order the first wire reaches them.
+pub fn crossings(wires: &[Wire]) -> Vec<Crossing> {
+    let mut crossings = Vec::new();
+    for (i, a) in wires.iter().enumerate() {
+        for (j, b) in wires.iter().enumerate().skip(i + 1) {
+            let mut points: Vec<(usize, Point)> = Vec::new();
+            for s in &a.segments {
+                for t in &b.segments {
+                    points.extend(
+                        s.intersections(t)
+                            .filter(|&p| p != ORIGIN)
+                            .map(|p| (s.steps + manhattan(s.start, p), p)),
+                    );
+                }
+            }
+            points.sort_unstable_by_key(|&(steps, p)| (steps, p.x, p.y));
+            let mut seen = HashSet::new();
+            for (_, point) in points {
+                if !seen.insert(point) {
+                    continue;
+                }
+                let steps = (a.steps_to(point).unwrap(), b.steps_to(point).unwrap());
+                crossings.push(Crossing {
+                    point,
+                    wires: (i, j),
+                    steps,
+                });
+            }
         }
     }
+    crossings
+}
+
+fn manhattan(a: Point, b: Point) -> usize {
+    ((a.x - b.x).abs() + (a.y - b.y).abs()) as usize
+}
+
+/// Draws the wires as an SVG image, north up, with the origin and every
+/// crossing circled.
+pub fn svg(wires: &[Wire]) -> String {
+    const COLORS: [&str; 6] = [
+        "crimson",
+        "royalblue",
+        "seagreen",
+        "darkorange",
+        "purple",
+        "teal",
+    ];
+    let points = wires
+        .iter()
+        .flat_map(|w| &w.segments)
+        .flat_map(|s| [s.start, s.end])
+        .chain(Some(ORIGIN));
+    let (mut min, mut max) = (ORIGIN, ORIGIN);
+    for p in points {
+        min = Point {
+            x: min.x.min(p.x),
+            y: min.y.min(p.y),
+        };
+        max = Point {
+            x: max.x.max(p.x),
+            y: max.y.max(p.y),
+        };
+    }
+    let margin = ((max.x - min.x).max(max.y - min.y) / 20).max(1);
+    let stroke = (margin as f64 / 5.0).max(0.2);
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        min.x - margin,
+        -max.y - margin,
+        max.x - min.x + 2 * margin,
+        max.y - min.y + 2 * margin
+    );
+    for (i, wire) in wires.iter().enumerate() {
+        let mut path: Vec<String> = vec!["0,0".to_owned()];
+        path.extend(
+            wire.segments
+                .iter()
+                .map(|s| format!("{},{}", s.end.x, -s.end.y)),
+        );
+        let _ = writeln!(
+            svg,
+            r#"  <polyline points="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+            path.join(" "),
+            COLORS[i % COLORS.len()],
+            stroke
+        );
+    }
+    let circles = crossings(wires).into_iter().map(|c| (c.point, "black"));
+    for (p, color) in Some((ORIGIN, "gold")).into_iter().chain(circles) {
+        let _ = writeln!(
+            svg,
+            r#"  <circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+            p.x,
+            -p.y,
+            stroke * 2.0,
+            color
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
 }
 
 #[cfg(test)]
@@ -103,16 +251,58 @@ mod test {
     #[test]
     fn d3() {
         let mut input = "R8,U5,L5,D3\nU7,R6,D4,L4";
-        let mut parsed = generator(input);
-        assert_eq!(part1(&parsed[..]), 6);
-        assert_eq!(part2(&parsed[..]), 30);
+        let mut parsed = generator(input).unwrap();
+        assert_eq!(part1(&parsed[..]), Ok(6));
+        assert_eq!(part2(&parsed[..]), Ok(30));
         input = "R75,D30,R83,U83,L12,D49,R71,U7,L72\nU62,R66,U55,R34,D71,R55,D58,R83";
-        parsed = generator(input);
-        assert_eq!(part1(&parsed[..]), 159);
-        assert_eq!(part2(&parsed[..]), 610);
+        parsed = generator(input).unwrap();
+        assert_eq!(part1(&parsed[..]), Ok(159));
+        assert_eq!(part2(&parsed[..]), Ok(610));
         input = "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51\nU98,R91,D20,R16,D67,R40,U7,R15,U6,R7";
-        parsed = generator(input);
-        assert_eq!(part1(&parsed[..]), 135);
-        assert_eq!(part2(&parsed[..]), 410);
+        parsed = generator(input).unwrap();
+        assert_eq!(part1(&parsed[..]), Ok(135));
+        assert_eq!(part2(&parsed[..]), Ok(410));
+    }
+
+    #[test]
+    fn crossings_of_many_wires() {
+        let wires = generator("R8,U5,L5,D3\nU7,R6,D4,L4\nU2,R10").unwrap();
+        let found: Vec<_> = crossings(&wires)
+            .iter()
+            .map(|c| (c.wires, c.point.x, c.point.y, c.steps))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ((0, 1), 6, 5, (15, 15)),
+                ((0, 1), 3, 3, (20, 20)),
+                ((0, 2), 8, 2, (10, 10)),
+                ((0, 2), 3, 2, (21, 5)),
+                ((1, 2), 0, 1, (1, 1)),
+                ((1, 2), 0, 2, (2, 2)),
+            ]
+        );
+        assert_eq!(part1(&wires), Ok(1));
+    }
+
+    #[test]
+    fn overlapping_wires() {
+        let wires = generator("R5\nU1,R1,D1,R3").unwrap();
+        let points: Vec<_> = crossings(&wires).iter().map(|c| c.point.x).collect();
+        assert_eq!(points, [1, 2, 3, 4]);
+        assert!(generator("R5,X3").is_err());
+        assert!(part1(&wires[..1]).is_err());
+    }
+
+    #[test]
+    fn render() {
+        let wires = generator("R8,U5,L5,D3\nU7,R6,D4,L4").unwrap();
+        let svg = svg(&wires);
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-1 -8 10 9\">")
+        );
+        assert!(svg.contains("points=\"0,0 8,0 8,-5 3,-5 3,-2\""));
+        assert_eq!(svg.matches("<circle").count(), 3);
+        assert!(svg.ends_with("</svg>\n"));
     }
 }
@@ -1,79 +1,322 @@
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::{unsigned, ws},
+};
+use nom::{character::complete::char, sequence::separated_pair};
+use std::{collections::HashMap, ops::RangeInclusive};
+
+const DAY: Day = Day::new(2019, 4);
+const DIGITS: usize = 6;
+
 #[aoc_generator(day4)]
-fn generator(input: &str) -> (usize, usize) {
-    let mut range = input.split('-').map(|s| s.parse::<usize>().unwrap());
-    (range.next().unwrap(), range.next().unwrap())
+fn generator(input: &str) -> Result<(u64, u64), AocError> {
+    let (low, high) = DAY.parse_with(input, ws(separated_pair(unsigned, char('-'), unsigned)))?;
+    if low > high {
+        return Err(DAY.error(format!("the range {}-{} is empty", low, high)));
+    }
+    Ok((low, high))
 }
 
 #[aoc(day4, part1)]
-fn part1(input: &(usize, usize)) -> usize {
-    (input.0..input.1)
-        .filter(|x| monotonic(*x))
-        .filter(|x| has_multiple(*x))
-        .count()
+fn part1(input: &(u64, u64)) -> Result<u64, AocError> {
+    Ok(Passwords::new(DIGITS, vec![Rule::adjacent_pair()]).count(input.0..=input.1))
 }
 
 #[aoc(day4, part2)]
-fn part2(input: &(usize, usize)) -> usize {
-    (input.0..input.1)
-        .filter(|x| monotonic(*x))
-        .filter(|x| has_double(*x))
-        .count()
+fn part2(input: &(u64, u64)) -> Result<u64, AocError> {
+    Ok(Passwords::new(DIGITS, vec![Rule::exact_pair()]).count(input.0..=input.1))
 }
 
-fn monotonic(value: usize) -> bool {
-    let mut v = value;
-    let mut last = 9;
-    while v > 0 {
-        if v % 10 > last {
-            return false;
-        }
-        last = v % 10;
-        v /= 10;
+/// A condition on the runs of equal digits in a password. Since the digits
+/// never decrease, equal digits always sit together in a single run.
+///
+/// Rules only look at run lengths, each checked against a range, which
+/// covers both parts of the puzzle. A rule can't depend on which digit a
+/// run is made of; the counting state would need to track that too.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// Some run's length is in the range.
+    Some(RangeInclusive<usize>),
+    /// Every run's length is in the range.
+    Every(RangeInclusive<usize>),
+}
+
+impl Rule {
+    /// Two adjacent digits are the same.
+    pub fn adjacent_pair() -> Self {
+        Rule::Some(2..=usize::MAX)
+    }
+
+    /// Two adjacent digits are the same and not part of a longer run.
+    pub fn exact_pair() -> Self {
+        Rule::Some(2..=2)
+    }
+
+    /// No digit repeats more than `length` times in a row.
+    pub fn max_run(length: usize) -> Self {
+        Rule::Every(1..=length)
     }
-    true
 }
 
-fn has_multiple(value: usize) -> bool {
-    let mut last: isize = -1;
-    let mut v = value;
-    while v > 0 {
-        if v as isize % 10 == last {
-            return true;
+/// The `digits`-digit numbers, zero padded, whose digits never decrease
+/// from left to right and that follow every rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Passwords {
+    digits: usize,
+    rules: Vec<Rule>,
+}
+
+/// Where a search is: the digits placed so far, the last one, how long its
+/// run is, and which `Rule::Some`s are already met.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct State {
+    position: usize,
+    last: u8,
+    run: usize,
+    met: u32,
+}
+
+impl Passwords {
+    /// Panics if `digits` is more than 19, the most a `u64` always holds.
+    pub fn new(digits: usize, rules: Vec<Rule>) -> Self {
+        assert!(digits <= 19, "passwords are at most 19 digits long");
+        assert!(rules.len() <= 32, "at most 32 rules are supported");
+        Passwords { digits, rules }
+    }
+
+    /// How many passwords are in `range`.
+    ///
+    /// A digit DP: the count of passwords up to some bound only depends on
+    /// the state of the search once the digits placed so far fall below the
+    /// bound, so each state is counted once however many prefixes reach it.
+    pub fn count(&self, range: RangeInclusive<u64>) -> u64 {
+        let (low, high) = (*range.start(), (*range.end()).min(self.largest()));
+        if low > high {
+            return 0;
         }
-        last = v as isize % 10;
-        v /= 10;
+        let below_low = match low.checked_sub(1) {
+            Some(n) => self.count_up_to(n),
+            None => 0,
+        };
+        self.count_up_to(high) - below_low
     }
-    false
-}
 
-fn has_double(value: usize) -> bool {
-    let s = value.to_string();
-    let mut v = Vec::with_capacity(s.len());
-    for c in s.chars() {
-        if let Some(&l) = v.last() {
-            if c == l {
-                v.push(c)
-            } else if v.len() == 2 {
-                return true;
-            } else {
-                v.clear();
-                v.push(c);
-            }
+    /// Every password in `range`, in increasing order. Only sequences whose
+    /// digits never decrease are ever visited.
+    pub fn list(&self, range: RangeInclusive<u64>) -> Vec<u64> {
+        let (low, high) = (*range.start(), (*range.end()).min(self.largest()));
+        let mut found = Vec::new();
+        if low <= high {
+            let bounds = (&self.digits_of(low)[..], &self.digits_of(high)[..]);
+            self.visit(bounds, self.start(), (true, true), 0, &mut found);
+        }
+        found
+    }
+
+    fn largest(&self) -> u64 {
+        10u64.pow(self.digits as u32) - 1
+    }
+
+    fn digits_of(&self, mut n: u64) -> Vec<u8> {
+        let mut digits = vec![0; self.digits];
+        for digit in digits.iter_mut().rev() {
+            *digit = (n % 10) as u8;
+            n /= 10;
+        }
+        digits
+    }
+
+    fn start(&self) -> State {
+        State {
+            position: 0,
+            last: 0,
+            run: 0,
+            met: 0,
+        }
+    }
+
+    /// The state after placing `digit`, or `None` if that breaks a rule.
+    fn place(&self, state: State, digit: u8) -> Option<State> {
+        let (run, met) = if state.position > 0 && digit == state.last {
+            (state.run + 1, state.met)
         } else {
-            v.push(c);
+            (1, self.close_run(state.run, state.met)?)
+        };
+        Some(State {
+            position: state.position + 1,
+            last: digit,
+            run,
+            met,
+        })
+    }
+
+    /// Checks a finished run of `length` digits against the rules.
+    fn close_run(&self, length: usize, mut met: u32) -> Option<u32> {
+        if length == 0 {
+            return Some(met);
+        }
+        for (i, rule) in self.rules.iter().enumerate() {
+            match rule {
+                Rule::Some(lengths) if lengths.contains(&length) => met |= 1 << i,
+                Rule::Every(lengths) if !lengths.contains(&length) => return None,
+                _ => {}
+            }
+        }
+        Some(met)
+    }
+
+    fn accepts(&self, state: State) -> bool {
+        let wanted = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| matches!(rule, Rule::Some(_)))
+            .fold(0, |met, (i, _)| met | 1 << i);
+        self.close_run(state.run, state.met) == Some(wanted)
+    }
+
+    fn count_up_to(&self, n: u64) -> u64 {
+        let bound = self.digits_of(n);
+        self.count_from(&bound, self.start(), true, &mut HashMap::new())
+    }
+
+    fn count_from(
+        &self,
+        bound: &[u8],
+        state: State,
+        tight: bool,
+        memo: &mut HashMap<State, u64>,
+    ) -> u64 {
+        if state.position == self.digits {
+            return u64::from(self.accepts(state));
+        }
+        if !tight {
+            if let Some(&count) = memo.get(&state) {
+                return count;
+            }
+        }
+        let top = if tight { bound[state.position] } else { 9 };
+        let mut count = 0;
+        for digit in state.last..=top {
+            if let Some(next) = self.place(state, digit) {
+                count += self.count_from(bound, next, tight && digit == top, memo);
+            }
+        }
+        if !tight {
+            memo.insert(state, count);
+        }
+        count
+    }
+
+    fn visit(
+        &self,
+        (low, high): (&[u8], &[u8]),
+        state: State,
+        (at_low, at_high): (bool, bool),
+        value: u64,
+        found: &mut Vec<u64>,
+    ) {
+        if state.position == self.digits {
+            if self.accepts(state) {
+                found.push(value);
+            }
+            return;
+        }
+        let bottom = if at_low { low[state.position] } else { 0 };
+        let top = if at_high { high[state.position] } else { 9 };
+        for digit in bottom.max(state.last)..=top {
+            if let Some(next) = self.place(state, digit) {
+                let tight = (at_low && digit == bottom, at_high && digit == top);
+                let value = value * 10 + u64::from(digit);
+                self.visit((low, high), next, tight, value, found);
+            }
         }
     }
-    v.len() == 2
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    type Check = fn(&[u8]) -> bool;
+
+    fn brute(passwords: &Passwords, range: RangeInclusive<u64>, check: Check) -> u64 {
+        range
+            .filter(|&n| {
+                let digits = passwords.digits_of(n);
+                digits.windows(2).all(|w| w[0] <= w[1]) && check(&digits)
+            })
+            .count() as u64
+    }
+
+    fn runs(digits: &[u8]) -> Vec<usize> {
+        let mut runs = vec![1];
+        for w in digits.windows(2) {
+            if w[0] == w[1] {
+                *runs.last_mut().unwrap() += 1;
+            } else {
+                runs.push(1);
+            }
+        }
+        runs
+    }
+
     #[test]
     fn d4() {
-        assert!(has_double(112233));
-        assert!(!has_double(123444));
-        assert!(has_double(111122));
+        let exact = Passwords::new(6, vec![Rule::exact_pair()]);
+        assert_eq!(exact.count(112233..=112233), 1);
+        assert_eq!(exact.count(123444..=123444), 0);
+        assert_eq!(exact.count(111122..=111122), 1);
+        let pair = Passwords::new(6, vec![Rule::adjacent_pair()]);
+        assert_eq!(pair.count(111111..=111111), 1);
+        assert_eq!(pair.count(223450..=223450), 0);
+        assert_eq!(pair.count(123789..=123789), 0);
+        assert_eq!(part1(&(111111, 111111)), Ok(1));
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let range = 0..=99_999;
+        let cases: [(Vec<Rule>, Check); 4] = [
+            (vec![Rule::adjacent_pair()], |d| {
+                runs(d).iter().any(|&r| r >= 2)
+            }),
+            (vec![Rule::exact_pair()], |d| runs(d).contains(&2)),
+            (vec![Rule::max_run(2)], |d| runs(d).iter().all(|&r| r <= 2)),
+            (vec![Rule::adjacent_pair(), Rule::max_run(3)], |d| {
+                let runs = runs(d);
+                runs.iter().any(|&r| r >= 2) && runs.iter().all(|&r| r <= 3)
+            }),
+        ];
+        for (rules, check) in cases.iter() {
+            let passwords = Passwords::new(5, rules.clone());
+            for &(low, high) in &[(0, 99_999), (12_345, 67_890), (45_678, 45_678), (1, 0)] {
+                let expected = if low <= high {
+                    brute(&passwords, low..=high, *check)
+                } else {
+                    0
+                };
+                assert_eq!(passwords.count(low..=high), expected, "{:?}", rules);
+                assert_eq!(passwords.list(low..=high).len() as u64, expected);
+            }
+            assert_eq!(
+                passwords.count(range.clone()),
+                passwords.list(range.clone()).len() as u64
+            );
+        }
+    }
+
+    #[test]
+    fn large() {
+        let passwords = Passwords::new(14, vec![Rule::exact_pair(), Rule::max_run(4)]);
+        let range = 10_000_000_000_000..=99_999_999_999_999;
+        let listed = passwords.list(range.clone());
+        assert_eq!(passwords.count(range), listed.len() as u64);
+        assert!(listed.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(listed[0], 11_112_222_333_344);
+
+        let everything = Passwords::new(19, Vec::new());
+        // Non-decreasing sequences of 19 digits: C(19 + 9, 9).
+        assert_eq!(everything.count(0..=u64::MAX), 6_906_900);
     }
 }
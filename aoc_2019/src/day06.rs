@@ -1,58 +1,325 @@
-use std::{collections::HashMap, iter};
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::word,
+};
+use nom::{character::complete::char, combinator::map, sequence::separated_pair};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{self, Display, Write},
+};
+
+const DAY: Day = Day::new(2019, 6);
 
 #[aoc_generator(day6)]
-fn generator(input: &str) -> HashMap<String, String> {
-    input
-        .lines()
-        .map(|line| {
-            let mut objs = line.split(')');
-            let parent = objs.next().unwrap();
-            let object = objs.next().unwrap();
-            (object.to_owned(), parent.to_owned())
-        })
-        .collect()
+fn generator(input: &str) -> Result<OrbitMap, AocError> {
+    let orbit = separated_pair(word, char(')'), word);
+    let orbits = DAY.parse_lines(
+        input,
+        map(orbit, |(center, body): (&str, &str)| {
+            (center.to_owned(), body.to_owned())
+        }),
+    )?;
+    OrbitMap::new(&orbits).map_err(|e| DAY.error(e))
 }
 
 #[aoc(day6, part1)]
-fn part1(orbital_map: &HashMap<String, String>) -> usize {
-    let mut num_orbits = 0;
-    for object in orbital_map.keys() {
-        let mut curr_object = object;
-        while let Some(parent) = orbital_map.get(curr_object) {
-            num_orbits += 1;
-            curr_object = parent;
-        }
-    }
-    num_orbits
+fn part1(orbits: &OrbitMap) -> Result<usize, AocError> {
+    Ok(orbits.total_orbits())
 }
 
 #[aoc(day6, part2)]
-fn part2(orbital_map: &HashMap<String, String>) -> usize {
-    let you_ancestors: HashMap<String, usize> =
-        iter::successors(orbital_map.get("YOU"), |last| orbital_map.get(&last[..]))
-            .enumerate()
-            .map(|(i, v)| (v.to_owned(), i))
+fn part2(orbits: &OrbitMap) -> Result<usize, AocError> {
+    // Transfers are counted between the objects YOU and SAN are orbiting.
+    let you = orbits.center("YOU");
+    let san = orbits.center("SAN");
+    you.zip(san)
+        .and_then(|(you, san)| orbits.transfers(you, san))
+        .ok_or_else(|| DAY.error("YOU and SAN do not orbit a common object"))
+}
+
+/// Which body orbits which, with bodies numbered in the order they first
+/// appear and their names mapped to those numbers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrbitMap {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    centers: Vec<Option<usize>>,
+    orbiters: Vec<Vec<usize>>,
+    /// How many bodies each one orbits, directly or indirectly.
+    depths: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OrbitError {
+    /// `body` is listed as orbiting both `first` and `second`.
+    MultipleCenters {
+        body: String,
+        first: String,
+        second: String,
+    },
+    /// `body` ends up orbiting itself.
+    Cycle { body: String },
+}
+
+impl OrbitMap {
+    /// Builds the map from `(center, body)` pairs, each meaning `body` orbits
+    /// `center`.
+    pub fn new<S: AsRef<str>>(orbits: &[(S, S)]) -> Result<Self, OrbitError> {
+        let mut map = OrbitMap {
+            names: Vec::new(),
+            ids: HashMap::new(),
+            centers: Vec::new(),
+            orbiters: Vec::new(),
+            depths: Vec::new(),
+        };
+        for (center, body) in orbits {
+            let center = map.intern(center.as_ref());
+            let body = map.intern(body.as_ref());
+            if let Some(first) = map.centers[body] {
+                if first != center {
+                    return Err(OrbitError::MultipleCenters {
+                        body: map.names[body].clone(),
+                        first: map.names[first].clone(),
+                        second: map.names[center].clone(),
+                    });
+                }
+                continue;
+            }
+            map.centers[body] = Some(center);
+            map.orbiters[center].push(body);
+        }
+        map.depths = map.measure()?;
+        Ok(map)
+    }
+
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(name.to_owned());
+        self.ids.insert(name.to_owned(), id);
+        self.centers.push(None);
+        self.orbiters.push(Vec::new());
+        id
+    }
+
+    /// Every body's depth, found breadth first from the bodies that orbit
+    /// nothing. With one center per body, any body that isn't reached that
+    /// way hangs off a cycle.
+    fn measure(&self) -> Result<Vec<usize>, OrbitError> {
+        let mut depths = vec![None; self.names.len()];
+        let mut queue: VecDeque<usize> = (0..self.names.len())
+            .filter(|&id| self.centers[id].is_none())
             .collect();
-    let mut steps = 0;
-    for ancestor in iter::successors(orbital_map.get("SAN"), |last| orbital_map.get(&last[..])) {
-        if you_ancestors.contains_key(ancestor) {
-            return steps + *you_ancestors.get(ancestor).unwrap();
+        for &root in &queue {
+            depths[root] = Some(0);
+        }
+        while let Some(id) = queue.pop_front() {
+            let depth = depths[id].map(|d| d + 1);
+            for &orbiter in &self.orbiters[id] {
+                depths[orbiter] = depth;
+                queue.push_back(orbiter);
+            }
+        }
+        match depths.iter().position(Option::is_none) {
+            None => Ok(depths.into_iter().flatten().collect()),
+            Some(mut id) => {
+                // Walking inwards as many times as there are bodies is sure
+                // to end up on the cycle itself.
+                for _ in 0..self.names.len() {
+                    id = self.centers[id].expect("every unreached body has a center");
+                }
+                Err(OrbitError::Cycle {
+                    body: self.names[id].clone(),
+                })
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn contains(&self, body: &str) -> bool {
+        self.ids.contains_key(body)
+    }
+
+    /// The body `body` directly orbits.
+    pub fn center(&self, body: &str) -> Option<&str> {
+        let center = self.centers[*self.ids.get(body)?]?;
+        Some(&self.names[center])
+    }
+
+    /// How many bodies `body` orbits, directly or indirectly.
+    pub fn depth(&self, body: &str) -> Option<usize> {
+        self.ids.get(body).map(|&id| self.depths[id])
+    }
+
+    /// The number of direct and indirect orbits in the whole map.
+    pub fn total_orbits(&self) -> usize {
+        self.depths.iter().sum()
+    }
+
+    /// How many orbital transfers it takes to go from orbiting `from` to
+    /// orbiting `to`, or `None` if either is missing or they share no center.
+    pub fn transfers(&self, from: &str, to: &str) -> Option<usize> {
+        let (mut a, mut b) = (*self.ids.get(from)?, *self.ids.get(to)?);
+        let mut count = 0;
+        while self.depths[a] > self.depths[b] {
+            a = self.centers[a]?;
+            count += 1;
+        }
+        while self.depths[b] > self.depths[a] {
+            b = self.centers[b]?;
+            count += 1;
+        }
+        while a != b {
+            a = self.centers[a]?;
+            b = self.centers[b]?;
+            count += 2;
+        }
+        Some(count)
+    }
+
+    /// Every body orbiting `body`, directly or indirectly, in depth first
+    /// order with each body's orbiters in the order they were listed.
+    pub fn orbiters(&self, body: &str) -> Option<Vec<&str>> {
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = self.orbiters[*self.ids.get(body)?]
+            .iter()
+            .rev()
+            .copied()
+            .collect();
+        while let Some(id) = stack.pop() {
+            found.push(&self.names[id][..]);
+            stack.extend(self.orbiters[id].iter().rev());
+        }
+        Some(found)
+    }
+
+    /// The number of direct and indirect orbits among `body` and the bodies
+    /// orbiting it, as if `body` orbited nothing.
+    pub fn subtree_orbits(&self, body: &str) -> Option<usize> {
+        let base = self.depth(body)?;
+        let orbiters = self.orbiters(body)?;
+        Some(
+            orbiters
+                .iter()
+                .map(|&o| self.depths[self.ids[o]] - base)
+                .sum(),
+        )
+    }
+
+    /// The map as a Graphviz digraph, with an edge from every center to each
+    /// of its orbiters.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph orbits {\n");
+        for (body, center) in self.centers.iter().enumerate() {
+            if let Some(center) = center {
+                let _ = writeln!(
+                    dot,
+                    "  {:?} -> {:?};",
+                    self.names[*center], self.names[body]
+                );
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl Display for OrbitError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrbitError::MultipleCenters {
+                body,
+                first,
+                second,
+            } => write!(fmt, "{} orbits both {} and {}", body, first, second),
+            OrbitError::Cycle { body } => write!(fmt, "{} ends up orbiting itself", body),
         }
-        steps += 1;
     }
-    0
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    const EXAMPLE: &str = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\n";
+
     #[test]
     fn d6() {
-        let input = " COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN\n";
-        let graph1 = generator(&input[0..46]);
-        assert_eq!(part1(&graph1), 42);
-        let graph2 = generator(input);
-        assert_eq!(part2(&graph2), 4);
+        let map = generator(EXAMPLE).unwrap();
+        assert_eq!(part1(&map), Ok(42));
+        let map = generator(&format!("{}K)YOU\nI)SAN\n", EXAMPLE)).unwrap();
+        assert_eq!(part2(&map), Ok(4));
+        assert!(part2(&generator(EXAMPLE).unwrap()).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn queries() {
+        let map = generator(EXAMPLE).unwrap();
+        assert_eq!(map.len(), 12);
+        assert_eq!(map.depth("L"), Some(7));
+        assert_eq!(map.depth("X"), None);
+        assert_eq!(map.center("COM"), None);
+        assert_eq!(map.center("G"), Some("B"));
+        assert_eq!(map.transfers("H", "L"), Some(8));
+        assert_eq!(map.transfers("D", "L"), Some(4));
+        assert_eq!(map.transfers("L", "L"), Some(0));
+        assert_eq!(map.orbiters("E"), Some(vec!["F", "J", "K", "L"]));
+        assert_eq!(map.orbiters("L"), Some(vec![]));
+        assert_eq!(map.subtree_orbits("E"), Some(1 + 1 + 2 + 3));
+        assert_eq!(map.subtree_orbits("COM"), Some(42));
+
+        let forest = OrbitMap::new(&[("A", "B"), ("C", "D")]).unwrap();
+        assert_eq!(forest.transfers("B", "D"), None);
+        assert_eq!(forest.total_orbits(), 2);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            OrbitMap::new(&[("A", "B"), ("C", "B")]),
+            Err(OrbitError::MultipleCenters {
+                body: "B".to_owned(),
+                first: "A".to_owned(),
+                second: "C".to_owned(),
+            })
+        );
+        assert!(OrbitMap::new(&[("A", "B"), ("A", "B")]).is_ok());
+        let cycle = OrbitMap::new(&[("COM", "A"), ("C", "D"), ("D", "E"), ("E", "C")]);
+        assert!(matches!(cycle, Err(OrbitError::Cycle { .. })));
+        assert_eq!(
+            OrbitMap::new(&[("X", "X")]),
+            Err(OrbitError::Cycle {
+                body: "X".to_owned()
+            })
+        );
+        assert!(generator("A)B\nC)B\n").is_err());
+    }
+
+    #[test]
+    fn dot() {
+        let map = generator("COM)B\nB)C\nB)G\n").unwrap();
+        assert_eq!(
+            map.to_dot(),
+            "digraph orbits {\n  \"COM\" -> \"B\";\n  \"B\" -> \"C\";\n  \"B\" -> \"G\";\n}\n"
+        );
+    }
+
+    #[test]
+    fn long_chain() {
+        let orbits: Vec<(String, String)> = (0..100_000)
+            .map(|i| (i.to_string(), (i + 1).to_string()))
+            .collect();
+        let map = OrbitMap::new(&orbits).unwrap();
+        assert_eq!(map.total_orbits(), 100_000 * 100_001 / 2);
+        assert_eq!(map.transfers("0", "100000"), Some(100_000));
+    }
+}
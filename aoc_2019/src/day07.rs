@@ -1,17 +1,23 @@
 use std::{collections::VecDeque, iter};
 
 use crate::intcode::*;
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::integer_list,
+};
 
 use crossbeam::{channel, thread};
 use itertools::Itertools;
 
+const DAY: Day = Day::new(2019, 7);
+
 #[aoc_generator(day7)]
-fn generator(input: &str) -> Vec<isize> {
-    input.split(',').map(|v| v.parse().unwrap()).collect()
+fn generator(input: &str) -> Result<Vec<isize>, AocError> {
+    DAY.parse_with(input, integer_list(","))
 }
 
 #[aoc(day7, part1)]
-fn part1(program: &[isize]) -> isize {
+fn part1(program: &[isize]) -> Result<isize, AocError> {
     let (send, recv) = channel::bounded(120);
     thread::scope(|s| {
         for phase_setting in (0..=4).permutations(5) {
@@ -23,23 +29,24 @@ fn part1(program: &[isize]) -> isize {
                 let out_fn = |v| unsafe {
                     *out_ptr = v;
                 };
+                let mut result = Ok(());
                 for i in 0..5 {
                     let input =
                         iter::from_fn(|| get_input(i, &mut in_access[..], &phase_setting[..], out));
                     let mut amp = IntcodeComputer::new(program, input, out_fn);
-                    amp.run().unwrap();
+                    result = result.and(amp.run());
                 }
-                sender.send(out).unwrap();
+                sender.send(result.map(|_| out)).unwrap();
             });
         }
     })
     .unwrap();
     drop(send);
-    recv.iter().max().unwrap()
+    max_signal(recv)
 }
 
 #[aoc(day7, part2)]
-fn part2(program: &[isize]) -> isize {
+fn part2(program: &[isize]) -> Result<isize, AocError> {
     let (send, recv) = channel::bounded(128);
     thread::scope(|s| {
         for phase_setting in (5..=9).permutations(5) {
@@ -64,18 +71,31 @@ fn part2(program: &[isize]) -> isize {
                     let out_fn = |x| {
                         if sender.send(x).is_err() && i == NUM_AMPS - 1 {
                             // The first amplifier has halted
-                            result_sender.send(x).unwrap();
+                            result_sender.send(Ok(x)).unwrap();
                         }
                     };
                     let mut ic = IntcodeComputer::new(&program, receiver, out_fn);
-                    ic.run().unwrap();
+                    if let Err(e) = ic.run() {
+                        result_sender.send(Err(e)).unwrap();
+                    }
                 });
             }
         }
     })
     .unwrap();
     drop(send);
-    recv.iter().max().unwrap()
+    max_signal(recv)
+}
+
+fn max_signal(
+    results: channel::Receiver<Result<isize, InvalidInstruction>>,
+) -> Result<isize, AocError> {
+    let mut max = None;
+    for result in results {
+        let signal = result.map_err(|e| DAY.error(e))?;
+        max = max.max(Some(signal));
+    }
+    max.ok_or_else(|| DAY.error("no amplifier produced a signal"))
 }
 
 fn get_input(n: usize, in_access: &mut [usize], values: &[usize], out: isize) -> Option<isize> {
@@ -94,17 +114,18 @@ mod test {
     #[test]
     fn d7p1() {
         let input = "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0";
-        let v = generator(input);
+        let v = generator(input).unwrap();
         let a1 = part1(&v);
-        assert_eq!(a1, 43210);
+        assert_eq!(a1, Ok(43210));
+        assert!(part1(&[42]).is_err());
     }
 
     #[test]
     fn d7p2() {
         let input =
             "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
-        let v = generator(input);
+        let v = generator(input).unwrap();
         let a2 = part2(&v);
-        assert_eq!(a2, 139629729);
+        assert_eq!(a2, Ok(139629729));
     }
 }
@@ -1,53 +1,44 @@
-type Layer = [[u8; 25]; 6];
+use aoc_utils::{
+    error::{AocError, Day},
+    grid::Grid,
+    ocr,
+    parse::ws,
+};
+use nom::{character::complete::one_of, combinator::map, error::context, multi::many1};
+
+const DAY: Day = Day::new(2019, 8);
+const WIDTH: usize = 25;
+const HEIGHT: usize = 6;
+
+type Layer = Grid<u8>;
 
 #[aoc_generator(day8)]
-fn generator(input: &str) -> Vec<Layer> {
-    let mut values = input.chars().map(|v| v.to_string().parse().unwrap());
-    let layers = input.len() / (6 * 25);
-    let mut v: Vec<Layer> = Vec::with_capacity(layers);
-    for _ in 0..layers {
-        let mut layer: [[u8; 25]; 6] = [[0; 25]; 6];
-        for row in &mut layer {
-            for value in row.iter_mut().take(25) {
-                *value = values.next().unwrap();
-            }
-        }
-        v.push(layer);
+fn generator(input: &str) -> Result<Vec<Layer>, AocError> {
+    let pixel = map(one_of("012"), |v| v as u8 - b'0');
+    let values = DAY.parse_with(input, ws(many1(context("a pixel", pixel))))?;
+    if values.is_empty() || values.len() % (WIDTH * HEIGHT) != 0 {
+        return Err(DAY.error(format!(
+            "{} pixels do not make up whole {}x{} layers",
+            values.len(),
+            WIDTH,
+            HEIGHT
+        )));
     }
-    v
+    values
+        .chunks(WIDTH * HEIGHT)
+        .map(|layer| Grid::from_vec(WIDTH, HEIGHT, layer.to_vec()).map_err(|e| DAY.error(e)))
+        .collect()
 }
 
 #[aoc(day8, part1)]
-fn part1(image: &[Layer]) -> usize {
-    let mut fewest_zero = 25 * 6 + 1;
-    let mut fewest_zero_layer: &Layer = &image[1];
-    for layer in image {
-        let mut zeros = 0;
-        for row in layer.iter().take(6) {
-            for value in row.iter().take(25) {
-                if *value == 0 {
-                    zeros += 1;
-                }
-            }
-        }
-        if zeros < fewest_zero {
-            fewest_zero = zeros;
-            fewest_zero_layer = &layer;
-        }
-    }
-    let mut ones = 0;
-    let mut twos = 0;
-    for row in fewest_zero_layer.iter().take(6) {
-        for value in row.iter().take(25) {
-            match value {
-                //test
-                1 => ones += 1,
-                2 => twos += 1,
-                _ => {}
-            }
-        }
-    }
-    ones * twos
+fn part1(image: &[Layer]) -> Result<usize, AocError> {
+    let fewest_zero_layer = image
+        .iter()
+        .min_by_key(|layer| layer.iter().filter(|v| **v == 0).count())
+        .ok_or_else(|| DAY.error("the image has no layers"))?;
+    let ones = fewest_zero_layer.iter().filter(|v| **v == 1).count();
+    let twos = fewest_zero_layer.iter().filter(|v| **v == 2).count();
+    Ok(ones * twos)
 }
 
 #[derive(Copy, Clone)]
@@ -77,28 +68,14 @@ fn layer_pixel(px_front: PixelColor, px_back: PixelColor) -> PixelColor {
 }
 
 #[aoc(day8, part2)]
-fn part2(input: &[Layer]) -> String {
-    let mut image = [[PixelColor::Black; 25]; 6];
+fn part2(input: &[Layer]) -> Result<String, AocError> {
+    let mut image = Grid::new(WIDTH, HEIGHT, PixelColor::Black);
     for layer in input.iter().rev() {
-        for y in 0..6 {
-            for x in 0..25 {
-                image[y][x] = layer_pixel(PixelColor::from(layer[y][x]), image[y][x]);
-            }
+        for (pos, value) in layer.enumerate() {
+            image[pos] = layer_pixel(PixelColor::from(*value), image[pos]);
         }
     }
-    for row in &image {
-        for value in row.iter().take(25) {
-            match value {
-                PixelColor::Black => print!(" "),
-                PixelColor::White => print!("0"),
-                PixelColor::Transparent => print!("t"),
-            }
-        }
-        println!();
-    }
-
-    // Puzzle specific
-    "KCGEC".to_owned()
+    ocr::read_with(&image, |pixel| matches!(pixel, PixelColor::White)).map_err(|e| DAY.error(e))
 }
 
 #[cfg(test)]
@@ -112,7 +89,7 @@ mod test {
         let mut input = String::new();
         let mut f = File::open("input/2019/day8.txt").unwrap();
         f.read_to_string(&mut input).unwrap();
-        let v = generator(&input);
-        assert_eq!(part1(&v), 1584);
+        let v = generator(&input).unwrap();
+        assert_eq!(part1(&v), Ok(1584));
     }
 }
@@ -3,8 +3,14 @@ use std::collections::HashMap;
 use std::ops::Add;
 use std::f64::consts::PI;
 
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::char_grid,
+};
 use ordered_float::NotNan;
 
+const DAY: Day = Day::new(2019, 10);
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 struct Point {
     x: isize,
@@ -26,27 +32,42 @@ fn visible(asteroids: &HashSet<Point>, point: &Point) -> usize {
 }
 
 #[aoc_generator(day10)]
-fn generator(input: &str) -> HashSet<Point> {
-    let mut asteroids = HashSet::new();
-    input.lines().enumerate().for_each(|(y, line)| {
-        line.chars().enumerate().for_each(|(x, value)| {
-            if value == '#' {
-                asteroids.insert(Point { y: y as isize, x: x as isize });
-            }
+fn generator(input: &str) -> Result<HashSet<Point>, AocError> {
+    let map = DAY.parse_with(
+        input,
+        char_grid(|c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }),
+    )?;
+    Ok(map
+        .enumerate()
+        .filter(|(_, &asteroid)| asteroid)
+        .map(|((x, y), _)| Point {
+            x: x as isize,
+            y: y as isize,
         })
-    });
-    asteroids
+        .collect())
 }
 
 #[aoc(day10, part1)]
-fn part1(asteroids: &HashSet<Point>) -> usize {
-    asteroids.iter().map(|p| visible(&asteroids, p)).max().unwrap()
+fn part1(asteroids: &HashSet<Point>) -> Result<usize, AocError> {
+    asteroids
+        .iter()
+        .map(|p| visible(asteroids, p))
+        .max()
+        .ok_or_else(|| DAY.error("there are no asteroids"))
 }
 
 #[aoc(day10, part2)]
-fn part2(asteroids: &HashSet<Point>) -> isize {
+fn part2(asteroids: &HashSet<Point>) -> Result<isize, AocError> {
+    if asteroids.len() <= 200 {
+        let message = format!("only {} asteroids, the laser never vaporizes 200", asteroids.len());
+        return Err(DAY.error(message));
+    }
     let mut asteroids = asteroids.clone();
-    let laser = asteroids.iter().max_by_key(|n| visible(&asteroids, n)).unwrap().clone();
+    let laser = *asteroids.iter().max_by_key(|n| visible(&asteroids, n)).unwrap();
     asteroids.remove(&laser);
     let mut asteroids: Vec<_> = asteroids.into_iter().collect();
     asteroids.sort_by_key(|x| NotNan::new((x.x as f64 - laser.x as f64).hypot(x.y as f64 - laser.y as f64)).unwrap());
@@ -55,7 +76,7 @@ fn part2(asteroids: &HashSet<Point>) -> isize {
     }).collect();
     asteroids.sort_unstable_by(|o, b| ranks.get(b).cmp(&ranks.get(o)).then(laser.angle(b).partial_cmp(&laser.angle(o)).unwrap()));
     let p = asteroids[199];
-    (p.x * 100) + p.y
+    Ok((p.x * 100) + p.y)
 }
 
 impl Add for Point {
@@ -73,8 +94,8 @@ mod test {
     #[test]
     fn d10p1() {
         let input = ".#..#\n.....\n#####\n....#\n...##\n";
-        let set = generator(input);
-        assert_eq!(part1(&set), 8);
+        let set = generator(input).unwrap();
+        assert_eq!(part1(&set), Ok(8));
         let center = Point{x: 3, y: 2};
         assert_eq!(center.angle(&Point{x: 5, y: 3}), 2f64.atan2(-1f64));
     }
@@ -1,10 +1,15 @@
 use crate::intcode::IntcodeComputer;
 use crate::utils::{Direction, Point};
+use aoc_utils::{
+    error::{AocError, Day},
+    grid::Grid,
+    ocr,
+    parse::integer_list,
+};
 
-use crossbeam::{thread, channel};
+use crossbeam::{channel, thread};
 
 use std::collections::HashMap;
-use std::cmp;
 
 #[derive(Debug)]
 struct Painter {
@@ -15,10 +20,34 @@ struct Painter {
 impl Painter {
     fn turn(&mut self, dir: isize) {
         self.direction = match self.direction {
-            Direction::North => if dir == 0 {Direction::West} else {Direction::East},
-            Direction::South => if dir == 0 {Direction::East} else {Direction::West},
-            Direction::East => if dir == 0 {Direction::North} else {Direction::South},
-            Direction::West => if dir == 0 {Direction::South} else {Direction::North},
+            Direction::North => {
+                if dir == 0 {
+                    Direction::West
+                } else {
+                    Direction::East
+                }
+            }
+            Direction::South => {
+                if dir == 0 {
+                    Direction::East
+                } else {
+                    Direction::West
+                }
+            }
+            Direction::East => {
+                if dir == 0 {
+                    Direction::North
+                } else {
+                    Direction::South
+                }
+            }
+            Direction::West => {
+                if dir == 0 {
+                    Direction::South
+                } else {
+                    Direction::North
+                }
+            }
         }
     }
 
@@ -32,62 +61,141 @@ impl Painter {
     }
 }
 
+const DAY: Day = Day::new(2019, 11);
+const BLACK: isize = 0;
+const WHITE: isize = 1;
+const ORIGIN: Point = Point { x: 0, y: 0 };
+
 #[aoc_generator(day11)]
-fn generator(input: &str) -> Vec<isize> {
-    input.split(',').map(|v| v.parse().unwrap()).collect()
+fn generator(input: &str) -> Result<Vec<isize>, AocError> {
+    DAY.parse_with(input, integer_list(","))
+}
+
+#[aoc(day11, part1)]
+fn part1(program: &[isize]) -> Result<usize, AocError> {
+    Ok(paint(program, BLACK)?.len())
 }
 
 #[aoc(day11, part2)]
-fn part2(_: &[isize]) -> String {
-    println!("Puzzle Specific Implementation!!!");
-    String::from("LBJHEKLH")
+fn part2(program: &[isize]) -> Result<String, AocError> {
+    let hull = paint(program, WHITE)?;
+    ocr::read(&render(&hull)).map_err(|e| DAY.error(e))
 }
 
-#[aoc(day11, part1)]
-fn part1(program: &[isize]) -> usize {
+/// Draws the white panels of `hull`, with north at the top.
+fn render(hull: &HashMap<Point, isize>) -> Grid<bool> {
+    let x_min = hull.keys().map(|p| p.x).min().unwrap_or(0);
+    let x_max = hull.keys().map(|p| p.x).max().unwrap_or(0);
+    let y_min = hull.keys().map(|p| p.y).min().unwrap_or(0);
+    let y_max = hull.keys().map(|p| p.y).max().unwrap_or(0);
+    let width = (x_max - x_min + 1) as usize;
+    let height = (y_max - y_min + 1) as usize;
+    Grid::from_fn(width, height, |(x, y)| {
+        let point = Point {
+            x: x_min + x as isize,
+            y: y_max - y as isize,
+        };
+        hull.get(&point) == Some(&WHITE)
+    })
+}
+
+/// Runs the painting robot over a hull whose starting panel has the color
+/// `start`, returning the color of every panel it painted.
+fn paint(program: &[isize], start: isize) -> Result<HashMap<Point, isize>, AocError> {
     let (input, comp_recv) = channel::bounded(0);
     let (comp_send, output) = channel::bounded(0);
     let mut grid = HashMap::new();
-    grid.insert(Point{x: 0, y: 0}, 1);
-    let mut x_min = 0;
-    let mut x_max = 0;
-    let mut y_min = 0;
-    let mut y_max = 0;
-    thread::scope(|s| {
-        s.spawn(|_| {
-           let mut comp = IntcodeComputer::new(program, comp_recv, |x| comp_send.send(x).unwrap());
-           comp.run().unwrap();
+    let (run, painted) = thread::scope(|s| {
+        // The computer owns its end of the output channel, so the channel
+        // closes as soon as the program stops.
+        let robot = s.spawn(move |_| {
+            let mut comp = IntcodeComputer::new(program, comp_recv, |x| comp_send.send(x).unwrap());
+            comp.run()
         });
-        let mut painter = Painter {point: Point{ x: 0, y: 0}, direction: Direction::North};
-        loop {
-            x_min = cmp::min(painter.point.x, x_min);
-            x_max = cmp::max(painter.point.x, x_max);
-            y_min = cmp::min(painter.point.y, y_min);
-            y_max = cmp::max(painter.point.y, y_max);
-            let panel_color: &mut isize = grid.entry(painter.point).or_insert(0);
-            if input.send(*panel_color).is_err() {
-                break;
-            };
-            if let Ok(new_color) = output.recv() {
-                *panel_color = new_color;
-                let turn = output.recv().unwrap();
-                painter.turn(turn);
-                painter.step();
+        let mut painter = Painter {
+            point: ORIGIN,
+            direction: Direction::North,
+        };
+        let painted = loop {
+            let unpainted = if painter.point == ORIGIN {
+                start
             } else {
-                break;
+                BLACK
+            };
+            let panel_color = grid.get(&painter.point).copied().unwrap_or(unpainted);
+            if input.send(panel_color).is_err() {
+                break Ok(());
+            };
+            let new_color = match output.recv() {
+                Ok(color) => color,
+                Err(_) => break Ok(()),
+            };
+            grid.insert(painter.point, new_color);
+            match output.recv() {
+                Ok(turn) => painter.turn(turn),
+                Err(_) => break Err(DAY.error("the robot painted a panel without turning")),
             }
-        }
-    }).unwrap();
-    for y in (y_min..y_max+1).rev() {
-        for x in x_min+1..x_max-2 {
-            let color = grid.get(&Point{x, y}).unwrap_or(&0);
-            if *color == 1 {
-                print!("#");
-            } else {
-                print!(".");
+            painter.step();
+        };
+        (robot.join().unwrap(), painted)
+    })
+    .unwrap();
+    run.map_err(|e| DAY.error(e))?;
+    painted?;
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn registration() {
+        let rows = [
+            ".##..###.",
+            "#..#.#..#",
+            "#..#.###.",
+            "####.#..#",
+            "#..#.#..#",
+            "#..#.###.",
+        ];
+        let mut hull = HashMap::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let color = if c == '#' { WHITE } else { BLACK };
+                hull.insert(
+                    Point {
+                        x: x as isize,
+                        y: -(y as isize),
+                    },
+                    color,
+                );
             }
         }
-        println!();
+        assert_eq!(ocr::read(&render(&hull)), Ok("AB".to_owned()));
+    }
+
+    #[test]
+    fn painting_run() {
+        // Paints white, black and white, turning left each time, then halts
+        // on a panel it never painted.
+        let program = [
+            3, 0, 104, 1, 104, 0, 3, 0, 104, 0, 104, 0, 3, 0, 104, 1, 104, 0, 99,
+        ];
+        let hull = paint(&program, BLACK).unwrap();
+        assert_eq!(part1(&program), Ok(3));
+        assert_eq!(hull.get(&ORIGIN), Some(&WHITE));
+        assert_eq!(hull.get(&Point { x: -1, y: 0 }), Some(&BLACK));
+        assert_eq!(hull.get(&Point { x: -1, y: -1 }), Some(&WHITE));
+        assert_eq!(hull.get(&Point { x: 0, y: -1 }), None);
     }
-    grid.len()
-}
\ No newline at end of file
+
+    #[test]
+    fn stopping_early() {
+        // Halts, or fails, after reading a color without answering.
+        assert_eq!(paint(&[3, 0, 99], BLACK), Ok(HashMap::new()));
+        assert!(paint(&[3, 0, 42], BLACK).is_err());
+        // Paints without turning.
+        assert!(paint(&[3, 0, 104, 1, 99], BLACK).is_err());
+    }
+}
@@ -47,7 +47,9 @@ pub enum InvalidInstruction {
     MissingParams,
     NegativePositionalParam,
     Invalid(isize),
+    InvalidMode(isize),
     InvalidAddress,
+    MissingInput,
 }
 
 impl<I: Iterator<Item = U>, U: Borrow<isize>, O> IntcodeComputer<I, U, O>
@@ -70,60 +72,54 @@ where
         }
     }
 
+    /// Runs until the program halts with opcode 99.
     pub fn run(&mut self) -> Result<(), InvalidInstruction> {
-        while let Ok(intcode) = Intcode::try_from(&self.memory[self.pc..]) {
+        loop {
+            let code = self
+                .memory
+                .get(self.pc..)
+                .ok_or(InvalidInstruction::InvalidAddress)?;
+            let intcode = Intcode::try_from(code)?;
             let mut jmp = false;
             match intcode {
                 Intcode::Add(op1, op2, op3) => {
                     let (op1, op2) = (self.eval_param(op1)?, self.eval_param(op2)?);
                     let loc = op3.memory_address(self.relative_base)?;
-                    self.memory[loc] = op1 + op2;
+                    self.write(loc, op1 + op2)?;
                 }
                 Intcode::Mul(op1, op2, op3) => {
                     let (op1, op2) = (self.eval_param(op1)?, self.eval_param(op2)?);
                     let loc = op3.memory_address(self.relative_base)?;
-                    self.memory[loc] = op1 * op2;
+                    self.write(loc, op1 * op2)?;
                 }
                 Intcode::JNZ(op1, op2) => {
                     let (op1, op2) = (self.eval_param(op1)?, self.eval_param(op2)?);
                     if op1 != 0 {
                         jmp = true;
-                        self.pc = op2 as usize;
+                        self.pc = jump_target(op2)?;
                     }
                 }
                 Intcode::JZ(op1, op2) => {
                     let (op1, op2) = (self.eval_param(op1)?, self.eval_param(op2)?);
                     if op1 == 0 {
                         jmp = true;
-                        self.pc = op2 as usize;
+                        self.pc = jump_target(op2)?;
                     }
                 }
                 Intcode::LT(op1, op2, op3) => {
                     let loc = op3.memory_address(self.relative_base)?;
-                    self.memory[loc] = {
-                        let (op1, op2) = (self.eval_param(op1)?, self.eval_param(op2)?);
-                        if op1 < op2 {
-                            1
-                        } else {
-                            0
-                        }
-                    }
+                    let (op1, op2) = (self.eval_param(op1)?, self.eval_param(op2)?);
+                    self.write(loc, if op1 < op2 { 1 } else { 0 })?;
                 }
                 Intcode::EQ(op1, op2, op3) => {
                     let loc = op3.memory_address(self.relative_base)?;
-                    self.memory[loc] = {
-                        let op1 = self.eval_param(op1)?;
-                        let op2 = self.eval_param(op2)?;
-                        if op1 == op2 {
-                            1
-                        } else {
-                            0
-                        }
-                    }
+                    let (op1, op2) = (self.eval_param(op1)?, self.eval_param(op2)?);
+                    self.write(loc, if op1 == op2 { 1 } else { 0 })?;
                 }
                 Intcode::Input(op1) => {
                     let loc = op1.memory_address(self.relative_base)?;
-                    self.memory[loc] = *self.input.next().unwrap().borrow();
+                    let value = self.input.next().ok_or(InvalidInstruction::MissingInput)?;
+                    self.write(loc, *value.borrow())?;
                 }
                 Intcode::Output(op) => {
                     let op = self.eval_param(op)?;
@@ -133,13 +129,12 @@ where
                     let op = self.eval_param(op)?;
                     self.relative_base += op;
                 }
-                Intcode::Halt => break,
+                Intcode::Halt => return Ok(()),
             }
             if !jmp {
                 self.pc += intcode.size();
             }
         }
-        Ok(())
     }
 
     pub fn memory(&self) -> &[isize] {
@@ -149,16 +144,29 @@ where
     pub fn eval_param(&self, param: Param) -> Result<isize, InvalidInstruction> {
         match param.mode {
             AddressingMode::Immediate => Ok(param.value),
-            AddressingMode::Position => Ok(self.memory[param.value as usize]),
-            AddressingMode::Relative => {
-                if self.relative_base + param.value >= 0 {
-                    Ok(self.memory[(self.relative_base + param.value) as usize])
-                } else {
-                    Err(InvalidInstruction::InvalidAddress)
-                }
-            }
+            _ => self.read(param.memory_address(self.relative_base)?),
         }
     }
+
+    fn read(&self, address: usize) -> Result<isize, InvalidInstruction> {
+        self.memory
+            .get(address)
+            .copied()
+            .ok_or(InvalidInstruction::InvalidAddress)
+    }
+
+    fn write(&mut self, address: usize, value: isize) -> Result<(), InvalidInstruction> {
+        let cell = self
+            .memory
+            .get_mut(address)
+            .ok_or(InvalidInstruction::InvalidAddress)?;
+        *cell = value;
+        Ok(())
+    }
+}
+
+fn jump_target(address: isize) -> Result<usize, InvalidInstruction> {
+    usize::try_from(address).map_err(|_| InvalidInstruction::InvalidAddress)
 }
 
 impl TryFrom<&[isize]> for Intcode {
@@ -240,7 +248,7 @@ impl Intcode {
 
 impl Param {
     fn new(value: isize, mode: isize) -> Result<Self, InvalidInstruction> {
-        let mode = AddressingMode::from(mode);
+        let mode = AddressingMode::try_from(mode)?;
         if mode == AddressingMode::Position && value < 0 {
             return Err(InvalidInstruction::NegativePositionalParam);
         }
@@ -261,13 +269,15 @@ impl Param {
     }
 }
 
-impl From<isize> for AddressingMode {
-    fn from(input: isize) -> Self {
+impl TryFrom<isize> for AddressingMode {
+    type Error = InvalidInstruction;
+
+    fn try_from(input: isize) -> Result<Self, Self::Error> {
         match input {
-            0 => Self::Position,
-            1 => Self::Immediate,
-            2 => Self::Relative,
-            _ => unreachable!(),
+            0 => Ok(Self::Position),
+            1 => Ok(Self::Immediate),
+            2 => Ok(Self::Relative),
+            _ => Err(InvalidInstruction::InvalidMode(input)),
         }
     }
 }
@@ -281,9 +291,52 @@ impl Display for InvalidInstruction {
             InvalidInstruction::MissingParams => {
                 write!(fmt, "Instruction missing one or more parameters.")
             }
-            _ => write!(fmt, "Invalid Instruction"),
+            InvalidInstruction::NegativePositionalParam => {
+                write!(fmt, "Positional parameter is negative.")
+            }
+            InvalidInstruction::Invalid(code) => write!(fmt, "Invalid instruction {}.", code),
+            InvalidInstruction::InvalidMode(mode) => {
+                write!(fmt, "Invalid addressing mode {}.", mode)
+            }
+            InvalidInstruction::MissingInput => write!(fmt, "Ran out of input."),
         }
     }
 }
 
 impl Error for InvalidInstruction {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(program: &[isize], input: &[isize]) -> Result<Vec<isize>, InvalidInstruction> {
+        let mut output = Vec::new();
+        IntcodeComputer::new(program, input, |x| output.push(x)).run()?;
+        Ok(output)
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(run(&[3, 0, 4, 0, 99], &[7]), Ok(vec![7]));
+        assert_eq!(
+            run(&[4, 1000, 99], &[]),
+            Err(InvalidInstruction::InvalidAddress)
+        );
+        assert_eq!(
+            run(&[1101, 1, 1, 1000, 99], &[]),
+            Err(InvalidInstruction::InvalidAddress)
+        );
+        assert_eq!(
+            run(&[301, 0, 0, 0, 99], &[]),
+            Err(InvalidInstruction::InvalidMode(3))
+        );
+        assert_eq!(run(&[3, 0, 99], &[]), Err(InvalidInstruction::MissingInput));
+        assert_eq!(run(&[42], &[]), Err(InvalidInstruction::Invalid(42)));
+        assert_eq!(
+            run(&[1105, 1, -1], &[]),
+            Err(InvalidInstruction::InvalidAddress)
+        );
+        // Running off the end of memory is not a halt.
+        assert!(run(&[1101, 0, 0, 0], &[]).is_err());
+    }
+}
@@ -1,13 +1,19 @@
 use aoc_runner_derive::{aoc, aoc_generator};
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::unsigned,
+};
+
+const DAY: Day = Day::new(2021, 1);
 
 #[aoc_generator(day1)]
-pub fn generator(input: &str) -> Vec<usize> {
-    input.lines().map(|s| s.parse().unwrap()).collect()
+pub fn generator(input: &str) -> Result<Vec<usize>, AocError> {
+    DAY.parse_lines(input, unsigned)
 }
 
 #[aoc(day1, part1)]
-pub fn part1(input: &[usize]) -> usize {
-    input
+pub fn part1(input: &[usize]) -> Result<usize, AocError> {
+    Ok(input
         .iter()
         .fold((0, usize::MAX), |(num_descend, prev), next| {
             if *next > prev {
@@ -16,13 +22,13 @@ pub fn part1(input: &[usize]) -> usize {
                 (num_descend, *next)
             }
         })
-        .0
+        .0)
 }
 
 #[aoc(day1, part2)]
-pub fn part2(input: &[usize]) -> usize {
-    let mut sliding_window = SlidingWindow::new(input, 3);
-    sliding_window
+pub fn part2(input: &[usize]) -> Result<usize, AocError> {
+    let sliding_window = SlidingWindow::new(input, 3);
+    Ok(sliding_window
         .fold((0, usize::MAX), |(num_descend, prev), next| {
             let sum = next.iter().sum();
             if sum > prev {
@@ -31,7 +37,7 @@ pub fn part2(input: &[usize]) -> usize {
                 (num_descend, sum)
             }
         })
-        .0
+        .0)
 }
 
 pub struct SlidingWindow<'a> {
@@ -60,6 +66,6 @@ impl<'a> Iterator for SlidingWindow<'a> {
             return None;
         }
         self.cursor += 1;
-        Some((&self.data[start..end]))
+        Some(&self.data[start..end])
     }
 }
@@ -0,0 +1,144 @@
+use aoc_runner_derive::{aoc, aoc_generator};
+use aoc_utils::{
+    error::{AocError, Day},
+    parse::{labeled, unsigned, ws, IResult},
+};
+use nom::{branch::alt, combinator::map, error::context};
+
+const DAY: Day = Day::new(2021, 2);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Forward(i64),
+    Down(i64),
+    Up(i64),
+}
+
+/// How `down` and `up` are interpreted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Steering {
+    /// `down` and `up` change the depth directly.
+    Simple,
+    /// `down` and `up` change the aim, `forward` dives along it.
+    Aim,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Submarine {
+    pub steering: Steering,
+    pub horizontal: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+#[aoc_generator(day2)]
+pub fn generator(input: &str) -> Result<Vec<Command>, AocError> {
+    DAY.parse_lines(input, Command::parse)
+}
+
+#[aoc(day2, part1)]
+pub fn part1(course: &[Command]) -> Result<i64, AocError> {
+    Ok(Submarine::new(Steering::Simple).run(course).position())
+}
+
+#[aoc(day2, part2)]
+pub fn part2(course: &[Command]) -> Result<i64, AocError> {
+    Ok(Submarine::new(Steering::Aim).run(course).position())
+}
+
+impl Command {
+    fn parse(input: &str) -> IResult<'_, Self> {
+        context(
+            "`forward`, `down` or `up` followed by a distance",
+            alt((
+                map(labeled("forward", ws(unsigned)), Command::Forward),
+                map(labeled("down", ws(unsigned)), Command::Down),
+                map(labeled("up", ws(unsigned)), Command::Up),
+            )),
+        )(input)
+    }
+}
+
+impl Submarine {
+    pub fn new(steering: Steering) -> Self {
+        Self {
+            steering,
+            horizontal: 0,
+            depth: 0,
+            aim: 0,
+        }
+    }
+
+    pub fn step(&mut self, command: Command) {
+        match (self.steering, command) {
+            (Steering::Simple, Command::Forward(x)) => self.horizontal += x,
+            (Steering::Simple, Command::Down(x)) => self.depth += x,
+            (Steering::Simple, Command::Up(x)) => self.depth -= x,
+            (Steering::Aim, Command::Forward(x)) => {
+                self.horizontal += x;
+                self.depth += self.aim * x;
+            }
+            (Steering::Aim, Command::Down(x)) => self.aim += x,
+            (Steering::Aim, Command::Up(x)) => self.aim -= x,
+        }
+    }
+
+    /// The state after each command of `course`, in order.
+    pub fn replay<'a>(mut self, course: &'a [Command]) -> impl Iterator<Item = Submarine> + 'a {
+        course.iter().map(move |&command| {
+            self.step(command);
+            self
+        })
+    }
+
+    /// The state after the whole of `course`.
+    pub fn run(self, course: &[Command]) -> Self {
+        self.replay(course).last().unwrap_or(self)
+    }
+
+    /// The puzzle answer, horizontal position times depth.
+    pub fn position(&self) -> i64 {
+        self.horizontal * self.depth
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COURSE: &str = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n";
+
+    #[test]
+    fn example() {
+        let course = generator(COURSE).unwrap();
+        assert_eq!(course[3], Command::Up(3));
+        assert_eq!(part1(&course), Ok(150));
+        assert_eq!(part2(&course), Ok(900));
+    }
+
+    #[test]
+    fn replay() {
+        let course = generator(COURSE).unwrap();
+        let depths: Vec<_> = Submarine::new(Steering::Aim)
+            .replay(&course)
+            .map(|sub| (sub.horizontal, sub.depth, sub.aim))
+            .collect();
+        assert_eq!(
+            depths,
+            [
+                (5, 0, 0),
+                (5, 0, 5),
+                (13, 40, 5),
+                (13, 40, 2),
+                (13, 40, 10),
+                (15, 60, 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_command() {
+        let err = generator("forward 5\nbackward 2\n").unwrap_err();
+        assert_eq!(err.location, Some((2, "backward 2".to_owned())));
+    }
+}
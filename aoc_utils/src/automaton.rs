@@ -0,0 +1,365 @@
+//! One-dimensional cellular automata on an unbounded tape.
+//!
+//! A cell's next state depends on the `2 * radius + 1` cells centred on it.
+//! Rules are compiled into a lookup table indexed by that neighborhood read
+//! as a binary number, leftmost cell most significant.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display},
+};
+
+/// An unbounded row of cells of which finitely many are alive, stored as a
+/// bitset where bit `i` is cell `origin + i`. The row is kept trimmed so its
+/// first and last bits are live cells.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Tape {
+    bits: Vec<u64>,
+    len: usize,
+    origin: isize,
+}
+
+/// A lookup table from neighborhoods to the next state of their centre.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    radius: usize,
+    table: Vec<bool>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Automaton {
+    rules: Rules,
+    tape: Tape,
+    generation: u64,
+}
+
+/// A repeating pattern: from generation `start` on, every `period`
+/// generations the tape is the same shape moved `shift` cells to the right.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: u64,
+    pub period: u64,
+    pub shift: isize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AutomatonError {
+    /// A neighborhood of the wrong width was given to a radius `radius` rule.
+    Width { expected: usize, found: usize },
+    /// An all-dead neighborhood comes alive, which would fill the tape.
+    Unbounded,
+}
+
+impl Tape {
+    /// The cells `origin`, `origin + 1`, ... taken from `cells`.
+    pub fn new<I: IntoIterator<Item = bool>>(origin: isize, cells: I) -> Self {
+        let mut tape = Tape {
+            bits: Vec::new(),
+            len: 0,
+            origin,
+        };
+        let mut leading = true;
+        for alive in cells {
+            if leading && !alive {
+                tape.origin += 1;
+                continue;
+            }
+            leading = false;
+            if tape.len.is_multiple_of(64) {
+                tape.bits.push(0);
+            }
+            tape.bits[tape.len / 64] |= (alive as u64) << (tape.len % 64);
+            tape.len += 1;
+        }
+        while tape.len > 0 && !tape.bit(tape.len - 1) {
+            tape.len -= 1;
+        }
+        tape.bits.truncate(tape.len.div_ceil(64));
+        if tape.len == 0 {
+            tape.origin = 0;
+        }
+        tape
+    }
+
+    /// Reads `#` as alive and anything else as dead.
+    pub fn parse(origin: isize, text: &str) -> Self {
+        Tape::new(origin, text.chars().map(|c| c == '#'))
+    }
+
+    pub fn get(&self, cell: isize) -> bool {
+        let i = cell - self.origin;
+        i >= 0 && (i as usize) < self.len && self.bit(i as usize)
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    /// The leftmost live cell, or 0 for an empty tape.
+    pub fn origin(&self) -> isize {
+        self.origin
+    }
+
+    /// The number of cells from the leftmost to the rightmost live one.
+    pub fn span(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of live cells.
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// The positions of the live cells, left to right.
+    pub fn alive(&self) -> impl Iterator<Item = isize> + '_ {
+        (0..self.len)
+            .filter(move |&i| self.bit(i))
+            .map(move |i| self.origin + i as isize)
+    }
+
+    /// The same tape moved `by` cells to the right.
+    pub fn shifted(&self, by: isize) -> Self {
+        let mut tape = self.clone();
+        if !tape.is_empty() {
+            tape.origin += by;
+        }
+        tape
+    }
+
+    /// Whether `other` is this tape moved by some number of cells.
+    pub fn same_shape(&self, other: &Tape) -> bool {
+        self.len == other.len && self.bits == other.bits
+    }
+
+    /// The cells `from..=to` as `#` and `.`.
+    pub fn render(&self, from: isize, to: isize) -> String {
+        (from..=to)
+            .map(|cell| if self.get(cell) { '#' } else { '.' })
+            .collect()
+    }
+}
+
+impl Display for Tape {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let end = self.origin + self.len as isize - 1;
+        write!(fmt, "{}", self.render(self.origin, end))
+    }
+}
+
+impl Rules {
+    /// Rules under which every cell dies.
+    pub fn new(radius: usize) -> Self {
+        Rules {
+            radius,
+            table: vec![false; 1 << (2 * radius + 1)],
+        }
+    }
+
+    /// The radius 1 rule with the given Wolfram code.
+    pub fn elementary(code: u8) -> Self {
+        Rules {
+            radius: 1,
+            table: (0..8).map(|i| code & (1 << i) != 0).collect(),
+        }
+    }
+
+    pub fn radius(&self) -> usize {
+        self.radius
+    }
+
+    /// Makes the centre of `neighborhood` come alive (or die) next generation.
+    pub fn insert(&mut self, neighborhood: &[bool], alive: bool) -> Result<(), AutomatonError> {
+        let expected = 2 * self.radius + 1;
+        if neighborhood.len() != expected {
+            return Err(AutomatonError::Width {
+                expected,
+                found: neighborhood.len(),
+            });
+        }
+        let index = neighborhood
+            .iter()
+            .fold(0, |index, &cell| (index << 1) | cell as usize);
+        self.table[index] = alive;
+        Ok(())
+    }
+
+    fn next(&self, tape: &Tape, cell: isize) -> bool {
+        let r = self.radius as isize;
+        let index = (cell - r..=cell + r).fold(0, |index, c| (index << 1) | tape.get(c) as usize);
+        self.table[index]
+    }
+
+    /// Advances `tape` by one generation.
+    pub fn apply(&self, tape: &Tape) -> Tape {
+        let r = self.radius as isize;
+        let first = tape.origin - r;
+        let last = tape.origin + tape.len as isize - 1 + r;
+        Tape::new(first, (first..=last).map(|cell| self.next(tape, cell)))
+    }
+}
+
+impl Automaton {
+    pub fn new(rules: Rules, tape: Tape) -> Result<Self, AutomatonError> {
+        if rules.table[0] {
+            return Err(AutomatonError::Unbounded);
+        }
+        Ok(Automaton {
+            rules,
+            tape,
+            generation: 0,
+        })
+    }
+
+    pub fn tape(&self) -> &Tape {
+        &self.tape
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn step(&mut self) {
+        self.tape = self.rules.apply(&self.tape);
+        self.generation += 1;
+    }
+
+    /// Steps until the tape repeats its shape, giving up after `limit`
+    /// generations. A cycle with a non-zero shift is a glider.
+    pub fn find_cycle(&mut self, limit: u64) -> Option<Cycle> {
+        let mut seen = HashMap::new();
+        let end = self.generation + limit;
+        loop {
+            let key = (self.tape.bits.clone(), self.tape.len);
+            if let Some(&(start, origin)) = seen.get(&key) {
+                return Some(Cycle {
+                    start,
+                    period: self.generation - start,
+                    shift: self.tape.origin - origin,
+                });
+            }
+            if self.generation == end {
+                return None;
+            }
+            seen.insert(key, (self.generation, self.tape.origin));
+            self.step();
+        }
+    }
+
+    /// Moves on to generation `target`, skipping whole cycles once the tape
+    /// starts repeating.
+    pub fn advance(&mut self, target: u64) {
+        let mut seen = HashMap::new();
+        while self.generation < target {
+            let key = (self.tape.bits.clone(), self.tape.len);
+            if let Some(&(start, origin)) = seen.get(&key) {
+                let period = self.generation - start;
+                let shift = self.tape.origin - origin;
+                let cycles = (target - self.generation) / period;
+                self.tape = self.tape.shifted(shift * cycles as isize);
+                self.generation += cycles * period;
+                seen.clear();
+                continue;
+            }
+            seen.insert(key, (self.generation, self.tape.origin));
+            self.step();
+        }
+    }
+}
+
+impl Iterator for Automaton {
+    type Item = Tape;
+
+    /// Steps and yields the new generation.
+    fn next(&mut self) -> Option<Tape> {
+        self.step();
+        Some(self.tape.clone())
+    }
+}
+
+impl Cycle {
+    pub fn is_glider(&self) -> bool {
+        self.shift != 0
+    }
+}
+
+impl Display for AutomatonError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AutomatonError::Width { expected, found } => write!(
+                fmt,
+                "expected a neighborhood of {} cells, found {}",
+                expected, found
+            ),
+            AutomatonError::Unbounded => {
+                write!(fmt, "empty neighborhoods come alive, filling the tape")
+            }
+        }
+    }
+}
+
+impl Error for AutomatonError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sierpinski() {
+        let mut automaton = Automaton::new(Rules::elementary(90), Tape::parse(0, "#")).unwrap();
+        let rows: Vec<_> = automaton
+            .by_ref()
+            .take(3)
+            .map(|t| t.render(-3, 3))
+            .collect();
+        assert_eq!(rows, ["..#.#..", ".#...#.", "#.#.#.#"]);
+        assert_eq!(automaton.tape().count(), 4);
+        assert_eq!(automaton.tape().to_string(), "#.#.#.#");
+        assert_eq!(automaton.tape().origin(), -3);
+    }
+
+    #[test]
+    fn cycles() {
+        // Every live cell moves one to the left.
+        let mut rules = Rules::new(1);
+        rules.insert(&[false, false, true], true).unwrap();
+        rules.insert(&[true, false, true], true).unwrap();
+        rules.insert(&[false, true, true], true).unwrap();
+        rules.insert(&[true, true, true], true).unwrap();
+        let tape = Tape::parse(5, "##.#");
+        let mut glider = Automaton::new(rules.clone(), tape.clone()).unwrap();
+        let cycle = glider.find_cycle(10).unwrap();
+        assert_eq!((cycle.start, cycle.period, cycle.shift), (0, 1, -1));
+        assert!(cycle.is_glider());
+
+        let mut glider = Automaton::new(rules, tape).unwrap();
+        glider.advance(1_000_000_000_000);
+        assert_eq!(glider.generation(), 1_000_000_000_000);
+        assert_eq!(glider.tape().origin(), 5 - 1_000_000_000_000);
+        assert_eq!(glider.tape().to_string(), "##.#");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Rules::new(2).insert(&[true], true),
+            Err(AutomatonError::Width {
+                expected: 5,
+                found: 1
+            })
+        );
+        assert_eq!(
+            Automaton::new(Rules::elementary(1), Tape::default()).unwrap_err(),
+            AutomatonError::Unbounded
+        );
+        assert!(Tape::parse(3, "...").is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+/// Identifies the puzzle an error belongs to. Every solution module keeps
+/// one as a constant and builds its errors through it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Day {
+    pub year: u16,
+    pub day: u8,
+}
+
+/// An error raised while parsing or solving a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AocError {
+    pub day: Day,
+    /// The 1-based line number and the offending text, if the error can be
+    /// pinned to a line of the input.
+    pub location: Option<(usize, String)>,
+    pub message: String,
+}
+
+impl Day {
+    pub const fn new(year: u16, day: u8) -> Self {
+        Self { year, day }
+    }
+
+    pub fn error<M: Display>(self, message: M) -> AocError {
+        AocError {
+            day: self,
+            location: None,
+            message: message.to_string(),
+        }
+    }
+
+    /// An error for the line with the 0-based index `line`.
+    pub fn line_error<M: Display>(self, line: usize, text: &str, message: M) -> AocError {
+        AocError {
+            day: self,
+            location: Some((line + 1, text.to_owned())),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for Day {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{} day {}", self.year, self.day)
+    }
+}
+
+impl Display for AocError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match &self.location {
+            Some((line, text)) => write!(
+                fmt,
+                "{}, line {}: {} (in `{}`)",
+                self.day, line, self.message, text
+            ),
+            None => write!(fmt, "{}: {}", self.day, self.message),
+        }
+    }
+}
+
+impl Error for AocError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Day = Day::new(2018, 1);
+
+    #[test]
+    fn messages() {
+        let err = DAY.line_error(3, "+x", "invalid digit found in string");
+        assert_eq!(
+            err.to_string(),
+            "2018 day 1, line 4: invalid digit found in string (in `+x`)"
+        );
+        assert_eq!(DAY.error("no answer").to_string(), "2018 day 1: no answer");
+    }
+}
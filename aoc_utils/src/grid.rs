@@ -0,0 +1,558 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    ops::{Index, IndexMut},
+};
+
+/// A `(x, y)` position inside a grid, with `(0, 0)` being the top left cell.
+pub type Pos = (usize, usize);
+
+const ORTHOGONAL: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const ADJACENT: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+/// A dense, row-major two dimensional container.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GridError {
+    /// The number of cells does not match `width * height`.
+    SizeMismatch { expected: usize, found: usize },
+    /// A row of a character map is not as wide as the first one.
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    pub fn from_fn<F>(width: usize, height: usize, mut f: F) -> Self
+    where
+        F: FnMut(Pos) -> T,
+    {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(&mut f)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Result<Self, GridError> {
+        if cells.len() != width * height {
+            return Err(GridError::SizeMismatch {
+                expected: width * height,
+                found: cells.len(),
+            });
+        }
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Builds a grid from a character map, one row per line, mapping every
+    /// character with `f`. All rows must have the same width.
+    pub fn parse<F>(input: &str, mut f: F) -> Result<Self, GridError>
+    where
+        F: FnMut(char) -> T,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+        for (row, line) in input.lines().enumerate() {
+            let before = cells.len();
+            cells.extend(line.chars().map(&mut f));
+            let found = cells.len() - before;
+            match width {
+                None => width = Some(found),
+                Some(expected) if expected != found => {
+                    return Err(GridError::RaggedRow {
+                        row,
+                        expected,
+                        found,
+                    })
+                }
+                _ => {}
+            }
+            height += 1;
+        }
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    /// Like [`Grid::parse`], but rows shorter than the longest line are
+    /// filled up with `pad`.
+    pub fn parse_padded<F>(input: &str, pad: T, mut f: F) -> Self
+    where
+        T: Clone,
+        F: FnMut(char) -> T,
+    {
+        let width = input.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+        for line in input.lines() {
+            let before = cells.len();
+            cells.extend(line.chars().map(&mut f));
+            cells.resize(before + width, pad.clone());
+            height += 1;
+        }
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        if self.contains(pos) {
+            Some(&self.cells[self.index(pos)])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.contains(pos) {
+            let i = self.index(pos);
+            Some(&mut self.cells[i])
+        } else {
+            None
+        }
+    }
+
+    /// Bounds-checked access with signed coordinates, convenient when
+    /// stepping off the edge is expected.
+    pub fn get_signed(&self, x: isize, y: isize) -> Option<&T> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+        self.get((x as usize, y as usize))
+    }
+
+    /// Access treating the grid as a torus, so any coordinate is valid
+    /// unless the grid is empty.
+    pub fn get_wrapping(&self, x: isize, y: isize) -> Option<&T> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        let x = x.rem_euclid(self.width as isize) as usize;
+        let y = y.rem_euclid(self.height as isize) as usize;
+        Some(&self.cells[self.index((x, y))])
+    }
+
+    pub fn set(&mut self, pos: Pos, value: T) -> Option<T> {
+        self.get_mut(pos).map(|cell| std::mem::replace(cell, value))
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn into_cells(self) -> Vec<T> {
+        self.cells
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// Iterates over every cell together with its position, in reading order.
+    pub fn enumerate(&self) -> impl Iterator<Item = (Pos, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i % width, i / width), cell))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a zero chunk size, an empty range does not.
+        let width = self.width.max(1);
+        self.cells.chunks(width).take(self.height)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column {} out of bounds", x);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// The up to four orthogonally adjacent positions inside the grid.
+    pub fn neighbors4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.offsets(pos, &ORTHOGONAL)
+    }
+
+    /// The up to eight adjacent positions, including diagonals, inside the grid.
+    pub fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.offsets(pos, &ADJACENT)
+    }
+
+    fn offsets<'a>(
+        &'a self,
+        (x, y): Pos,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = Pos> + 'a {
+        offsets.iter().filter_map(move |(dx, dy)| {
+            let x = x.checked_add_signed(*dx)?;
+            let y = y.checked_add_signed(*dy)?;
+            if self.contains((x, y)) {
+                Some((x, y))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// A borrowed rectangular window into the grid.
+    ///
+    /// # Panics
+    /// If the window does not fit inside the grid.
+    pub fn view(&self, (x, y): Pos, width: usize, height: usize) -> GridView<'_, T> {
+        assert!(
+            x + width <= self.width && y + height <= self.height,
+            "view out of bounds"
+        );
+        GridView {
+            grid: self,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Mirrors the grid along its main diagonal.
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.height, self.width, |(x, y)| self[(y, x)].clone())
+    }
+
+    pub fn rotate_cw(&self) -> Self
+    where
+        T: Clone,
+    {
+        let height = self.height;
+        Grid::from_fn(self.height, self.width, |(x, y)| {
+            self[(y, height - 1 - x)].clone()
+        })
+    }
+
+    pub fn rotate_ccw(&self) -> Self
+    where
+        T: Clone,
+    {
+        let width = self.width;
+        Grid::from_fn(self.height, self.width, |(x, y)| {
+            self[(width - 1 - y, x)].clone()
+        })
+    }
+
+    /// Mirrors the grid left to right.
+    pub fn flip_horizontal(&self) -> Self
+    where
+        T: Clone,
+    {
+        let width = self.width;
+        Grid::from_fn(self.width, self.height, |(x, y)| {
+            self[(width - 1 - x, y)].clone()
+        })
+    }
+
+    /// Mirrors the grid top to bottom.
+    pub fn flip_vertical(&self) -> Self
+    where
+        T: Clone,
+    {
+        let height = self.height;
+        Grid::from_fn(self.width, self.height, |(x, y)| {
+            self[(x, height - 1 - y)].clone()
+        })
+    }
+
+    /// Renders the grid one line per row, drawing every cell with `f`.
+    pub fn render<F>(&self, f: F) -> Render<'_, T, F>
+    where
+        F: Fn(&T) -> char,
+    {
+        Render { grid: self, f }
+    }
+
+    #[inline]
+    fn index(&self, (x, y): Pos) -> usize {
+        y * self.width + x
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        match self.get(pos) {
+            Some(cell) => cell,
+            None => panic!(
+                "{:?} is outside of a {}x{} grid",
+                pos, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(pos) {
+            Some(cell) => cell,
+            None => panic!("{:?} is outside of a {}x{} grid", pos, width, height),
+        }
+    }
+}
+
+impl Display for Grid<char> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.render(|c| *c).fmt(fmt)
+    }
+}
+
+pub struct Render<'a, T, F> {
+    grid: &'a Grid<T>,
+    f: F,
+}
+
+impl<T, F> Display for Render<'_, T, F>
+where
+    F: Fn(&T) -> char,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.grid.rows() {
+            for cell in row {
+                write!(fmt, "{}", (self.f)(cell))?;
+            }
+            writeln!(fmt)?;
+        }
+        Ok(())
+    }
+}
+
+/// A rectangular window into a [`Grid`]; positions are relative to the
+/// window's top left corner.
+#[derive(Copy, Clone, Debug)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<'a, T> GridView<'a, T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (x, y): Pos) -> Option<&'a T> {
+        if x < self.width && y < self.height {
+            self.grid.get((self.x + x, self.y + y))
+        } else {
+            None
+        }
+    }
+
+    pub fn row(&self, y: usize) -> &'a [T] {
+        assert!(y < self.height, "row {} out of bounds", y);
+        &self.grid.row(self.y + y)[self.x..self.x + self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &'a [T]> + '_ {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + '_ {
+        self.rows().flatten()
+    }
+
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.iter().cloned().collect(),
+        }
+    }
+}
+
+impl Display for GridError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::SizeMismatch { expected, found } => {
+                write!(fmt, "Expected {} cells, found {}.", expected, found)
+            }
+            GridError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                fmt,
+                "Row {} is {} cells wide, expected {}.",
+                row + 1,
+                found,
+                expected
+            ),
+        }
+    }
+}
+
+impl Error for GridError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = "#..\n.#.\n..#\n##.\n";
+
+    #[test]
+    fn parse_and_access() {
+        let grid = Grid::parse(MAP, |c| c == '#').unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 4));
+        assert_eq!(grid.get((1, 1)), Some(&true));
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.get_signed(-1, 0), None);
+        assert_eq!(grid.get_wrapping(-1, -1), Some(&false));
+        assert_eq!(grid.get_wrapping(3, 4), Some(&true));
+        assert_eq!(Grid::new(0, 3, false).get_wrapping(1, 1), None);
+        assert_eq!(grid.column(0).filter(|c| **c).count(), 2);
+        assert_eq!(
+            Grid::parse("##\n#\n", |c| c),
+            Err(GridError::RaggedRow {
+                row: 1,
+                expected: 2,
+                found: 1
+            })
+        );
+        let padded = Grid::parse_padded("##\n#\n", ' ', |c| c);
+        assert_eq!(padded.to_string(), "##\n# \n");
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(
+            grid.neighbors4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbors8((1, 1)).count(), 8);
+        assert_eq!(grid.neighbors8((2, 2)).count(), 3);
+    }
+
+    #[test]
+    fn transforms() {
+        let grid = Grid::parse("ab\ncd\nef\n", |c| c).unwrap();
+        assert_eq!(grid.transpose().to_string(), "ace\nbdf\n");
+        assert_eq!(grid.rotate_cw().to_string(), "eca\nfdb\n");
+        assert_eq!(grid.rotate_ccw().to_string(), "bdf\nace\n");
+        assert_eq!(grid.flip_horizontal().to_string(), "ba\ndc\nfe\n");
+        assert_eq!(grid.flip_vertical().to_string(), "ef\ncd\nab\n");
+        let view = grid.view((1, 1), 1, 2);
+        assert_eq!(view.to_grid().to_string(), "d\nf\n");
+        assert_eq!(view.get((0, 1)), Some(&'f'));
+        assert_eq!(
+            grid.render(|c| c.to_ascii_uppercase()).to_string(),
+            "AB\nCD\nEF\n"
+        );
+    }
+}
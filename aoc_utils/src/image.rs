@@ -0,0 +1,47 @@
+//! Writes grids out as binary Netpbm images, which most image viewers open.
+
+use crate::grid::Grid;
+use std::io::{self, Write};
+
+/// Writes `grid` as a greyscale PGM image, one pixel per cell, where
+/// `shade(cell)` runs from black at 0 to white at 255.
+pub fn write_pgm<T, W, F>(out: &mut W, grid: &Grid<T>, shade: F) -> io::Result<()>
+where
+    W: Write,
+    F: Fn(&T) -> u8,
+{
+    write!(out, "P5\n{} {}\n255\n", grid.width(), grid.height())?;
+    let pixels: Vec<u8> = grid.iter().map(shade).collect();
+    out.write_all(&pixels)
+}
+
+/// Writes `grid` as a PPM image, one pixel per cell, where `color(cell)` is
+/// its red, green and blue.
+pub fn write_ppm<T, W, F>(out: &mut W, grid: &Grid<T>, color: F) -> io::Result<()>
+where
+    W: Write,
+    F: Fn(&T) -> [u8; 3],
+{
+    write!(out, "P6\n{} {}\n255\n", grid.width(), grid.height())?;
+    let pixels: Vec<u8> = grid.iter().flat_map(color).collect();
+    out.write_all(&pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers() {
+        let grid = Grid::from_fn(3, 2, |(x, y)| x + y);
+        let mut pgm = Vec::new();
+        write_pgm(&mut pgm, &grid, |&v| (v * 100) as u8).unwrap();
+        assert_eq!(pgm, b"P5\n3 2\n255\n\x00\x64\xc8\x64\xc8\x2c");
+
+        let mut ppm = Vec::new();
+        write_ppm(&mut ppm, &grid, |&v| [v as u8, 0, 255]).unwrap();
+        assert_eq!(&ppm[..11], b"P6\n3 2\n255\n");
+        assert_eq!(&ppm[11..17], [0, 0, 255, 1, 0, 255]);
+        assert_eq!(ppm.len(), 11 + 18);
+    }
+}
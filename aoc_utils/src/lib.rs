@@ -0,0 +1,16 @@
+//! Puzzle-independent building blocks shared by the yearly solution crates.
+
+pub mod automaton;
+pub mod error;
+pub mod grid;
+pub mod image;
+pub mod life;
+pub mod ocr;
+pub mod parse;
+pub mod rect;
+pub mod ring;
+pub mod schedule;
+pub mod search;
+pub mod similarity;
+pub mod summed_area;
+pub mod voronoi;
@@ -0,0 +1,434 @@
+//! Two-dimensional cellular automata, the Game of Life and its relatives.
+//!
+//! A rule maps a cell and the states of its neighbors to the cell's next
+//! state. Cells beyond the edge of a bounded [`Board`], and every cell a
+//! [`Sparse`] board does not store, are in the `Default` state.
+
+use crate::grid::{Grid, Pos};
+use std::{collections::HashMap, hash::Hash};
+
+/// What a cell state needs to be: cheap to copy, with a quiescent default,
+/// and hashable for cycle detection.
+pub trait State: Copy + Default + Eq + Hash {}
+
+impl<T: Copy + Default + Eq + Hash> State for T {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// The eight surrounding cells.
+    Moore,
+    /// The four orthogonally adjacent cells.
+    VonNeumann,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Topology {
+    /// Cells past the edge are always in the default state.
+    Bounded,
+    /// Opposite edges are adjacent.
+    Toroidal,
+}
+
+/// From generation `start` on, the board repeats every `period` generations.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: u64,
+    pub period: u64,
+}
+
+/// A dense, finite board. Stepping writes into a second buffer which is then
+/// swapped in, so no cell sees a neighbor's new state.
+#[derive(Clone, Debug)]
+pub struct Board<T> {
+    front: Grid<T>,
+    back: Grid<T>,
+    neighborhood: Neighborhood,
+    topology: Topology,
+    generation: u64,
+}
+
+/// An unbounded board storing only the cells not in the default state. The
+/// rule must keep a cell with only default neighbors in the default state.
+#[derive(Clone, Debug)]
+pub struct Sparse<T> {
+    cells: HashMap<(isize, isize), T>,
+    neighborhood: Neighborhood,
+    generation: u64,
+}
+
+impl Neighborhood {
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Neighborhood::Moore => &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ],
+            Neighborhood::VonNeumann => &[(0, -1), (-1, 0), (1, 0), (0, 1)],
+        }
+    }
+}
+
+impl<T: State> Board<T> {
+    pub fn new(grid: Grid<T>, neighborhood: Neighborhood, topology: Topology) -> Self {
+        Board {
+            back: grid.clone(),
+            front: grid,
+            neighborhood,
+            topology,
+            generation: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid<T> {
+        &self.front
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn step<F>(&mut self, rule: F)
+    where
+        F: Fn(T, &[T]) -> T,
+    {
+        let (front, back) = (&self.front, &mut self.back);
+        for (i, cell) in back.cells_mut().iter_mut().enumerate() {
+            let pos = (i % front.width(), i / front.width());
+            *cell = next(front, pos, self.neighborhood, self.topology, &rule);
+        }
+        self.swap();
+    }
+
+    /// Like [`Board::step`], with the rows computed in parallel.
+    #[cfg(feature = "rayon")]
+    pub fn par_step<F>(&mut self, rule: F)
+    where
+        T: Send + Sync,
+        F: Fn(T, &[T]) -> T + Sync,
+    {
+        use rayon::prelude::*;
+
+        let (front, back) = (&self.front, &mut self.back);
+        let (neighborhood, topology) = (self.neighborhood, self.topology);
+        let width = front.width().max(1);
+        back.cells_mut()
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (x, cell) in row.iter_mut().enumerate() {
+                    *cell = next(front, (x, y), neighborhood, topology, &rule);
+                }
+            });
+        self.swap();
+    }
+
+    fn swap(&mut self) {
+        std::mem::swap(&mut self.front, &mut self.back);
+        self.generation += 1;
+    }
+
+    /// Steps until the board repeats, giving up after `limit` generations.
+    pub fn find_cycle<F>(&mut self, limit: u64, rule: F) -> Option<Cycle>
+    where
+        F: Fn(T, &[T]) -> T,
+    {
+        find_cycle(self, limit, |board| board.front.clone(), |b| b.step(&rule))
+    }
+
+    /// Moves on to generation `target`, skipping whole cycles once the board
+    /// starts repeating.
+    pub fn advance<F>(&mut self, target: u64, rule: F)
+    where
+        F: Fn(T, &[T]) -> T,
+    {
+        advance(self, target, |board| board.front.clone(), |b| b.step(&rule))
+    }
+}
+
+fn next<T, F>(
+    grid: &Grid<T>,
+    (x, y): Pos,
+    neighborhood: Neighborhood,
+    topology: Topology,
+    rule: &F,
+) -> T
+where
+    T: State,
+    F: Fn(T, &[T]) -> T,
+{
+    let mut neighbors = [T::default(); 8];
+    let offsets = neighborhood.offsets();
+    for (neighbor, (dx, dy)) in neighbors.iter_mut().zip(offsets) {
+        let (nx, ny) = (x as isize + dx, y as isize + dy);
+        *neighbor = match topology {
+            Topology::Bounded => grid.get_signed(nx, ny).copied().unwrap_or_default(),
+            Topology::Toroidal => grid.get_wrapping(nx, ny).copied().unwrap_or_default(),
+        };
+    }
+    rule(grid[(x, y)], &neighbors[..offsets.len()])
+}
+
+impl<T: State> Sparse<T> {
+    pub fn new(neighborhood: Neighborhood) -> Self {
+        Sparse {
+            cells: HashMap::new(),
+            neighborhood,
+            generation: 0,
+        }
+    }
+
+    /// A board holding `grid` with its top left corner at `(0, 0)`.
+    pub fn from_grid(grid: &Grid<T>, neighborhood: Neighborhood) -> Self {
+        let mut board = Sparse::new(neighborhood);
+        for ((x, y), &cell) in grid.enumerate() {
+            board.set((x as isize, y as isize), cell);
+        }
+        board
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn get(&self, pos: (isize, isize)) -> T {
+        self.cells.get(&pos).copied().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, pos: (isize, isize), cell: T) {
+        if cell == T::default() {
+            self.cells.remove(&pos);
+        } else {
+            self.cells.insert(pos, cell);
+        }
+    }
+
+    /// The number of cells not in the default state.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The cells not in the default state, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = ((isize, isize), T)> + '_ {
+        self.cells.iter().map(|(&pos, &cell)| (pos, cell))
+    }
+
+    /// The top left and bottom right corners of the smallest rectangle
+    /// holding every cell not in the default state.
+    pub fn bounds(&self) -> Option<((isize, isize), (isize, isize))> {
+        let xs = || self.cells.keys().map(|&(x, _)| x);
+        let ys = || self.cells.keys().map(|&(_, y)| y);
+        Some(((xs().min()?, ys().min()?), (xs().max()?, ys().max()?)))
+    }
+
+    /// The cells within [`Sparse::bounds`] as a dense grid.
+    pub fn to_grid(&self) -> Grid<T> {
+        match self.bounds() {
+            Some(((x0, y0), (x1, y1))) => {
+                let (width, height) = ((x1 - x0 + 1) as usize, (y1 - y0 + 1) as usize);
+                Grid::from_fn(width, height, |(x, y)| {
+                    self.get((x0 + x as isize, y0 + y as isize))
+                })
+            }
+            None => Grid::new(0, 0, T::default()),
+        }
+    }
+
+    pub fn step<F>(&mut self, rule: F)
+    where
+        F: Fn(T, &[T]) -> T,
+    {
+        let offsets = self.neighborhood.offsets();
+        let mut candidates: Vec<_> = self.cells.keys().copied().collect();
+        for &(x, y) in self.cells.keys() {
+            candidates.extend(offsets.iter().map(|(dx, dy)| (x + dx, y + dy)));
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let mut cells = HashMap::with_capacity(self.cells.len());
+        let mut neighbors = [T::default(); 8];
+        for (x, y) in candidates {
+            for (neighbor, (dx, dy)) in neighbors.iter_mut().zip(offsets) {
+                *neighbor = self.get((x + dx, y + dy));
+            }
+            let cell = rule(self.get((x, y)), &neighbors[..offsets.len()]);
+            if cell != T::default() {
+                cells.insert((x, y), cell);
+            }
+        }
+        self.cells = cells;
+        self.generation += 1;
+    }
+
+    fn key(&self) -> Vec<((isize, isize), T)> {
+        let mut cells: Vec<_> = self.iter().collect();
+        cells.sort_unstable_by_key(|&(pos, _)| pos);
+        cells
+    }
+
+    /// Steps until the board repeats in place, giving up after `limit`
+    /// generations.
+    pub fn find_cycle<F>(&mut self, limit: u64, rule: F) -> Option<Cycle>
+    where
+        F: Fn(T, &[T]) -> T,
+    {
+        find_cycle(self, limit, Sparse::key, |b| b.step(&rule))
+    }
+
+    /// Moves on to generation `target`, skipping whole cycles once the board
+    /// starts repeating in place.
+    pub fn advance<F>(&mut self, target: u64, rule: F)
+    where
+        F: Fn(T, &[T]) -> T,
+    {
+        advance(self, target, Sparse::key, |b| b.step(&rule))
+    }
+}
+
+trait Generations {
+    fn generation(&mut self) -> &mut u64;
+}
+
+impl<T> Generations for Board<T> {
+    fn generation(&mut self) -> &mut u64 {
+        &mut self.generation
+    }
+}
+
+impl<T> Generations for Sparse<T> {
+    fn generation(&mut self) -> &mut u64 {
+        &mut self.generation
+    }
+}
+
+fn find_cycle<W, K, F, S>(world: &mut W, limit: u64, key: F, mut step: S) -> Option<Cycle>
+where
+    W: Generations,
+    K: Hash + Eq,
+    F: Fn(&W) -> K,
+    S: FnMut(&mut W),
+{
+    let mut seen = HashMap::new();
+    let end = *world.generation() + limit;
+    loop {
+        let generation = *world.generation();
+        if let Some(&start) = seen.get(&key(world)) {
+            return Some(Cycle {
+                start,
+                period: generation - start,
+            });
+        }
+        if generation == end {
+            return None;
+        }
+        seen.insert(key(world), generation);
+        step(world);
+    }
+}
+
+fn advance<W, K, F, S>(world: &mut W, target: u64, key: F, mut step: S)
+where
+    W: Generations,
+    K: Hash + Eq,
+    F: Fn(&W) -> K,
+    S: FnMut(&mut W),
+{
+    let mut seen = HashMap::new();
+    while *world.generation() < target {
+        let generation = *world.generation();
+        let state = key(world);
+        if let Some(&start) = seen.get(&state) {
+            let period = generation - start;
+            *world.generation() += (target - generation) / period * period;
+            seen.clear();
+        } else {
+            seen.insert(state, generation);
+        }
+        if *world.generation() < target {
+            step(world);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn life(cell: bool, neighbors: &[bool]) -> bool {
+        let alive = neighbors.iter().filter(|&&n| n).count();
+        alive == 3 || (cell && alive == 2)
+    }
+
+    fn picture(rows: &str) -> Grid<bool> {
+        Grid::parse(rows, |c| c == '#').unwrap()
+    }
+
+    #[test]
+    fn bounded() {
+        let blinker = picture(".....\n..#..\n..#..\n..#..\n.....");
+        let mut board = Board::new(blinker.clone(), Neighborhood::Moore, Topology::Bounded);
+        board.step(life);
+        assert_eq!(board.grid(), &picture(".....\n.....\n.###.\n.....\n....."));
+        assert_eq!(
+            board.find_cycle(10, life),
+            Some(Cycle {
+                start: 1,
+                period: 2
+            })
+        );
+
+        let mut board = Board::new(blinker.clone(), Neighborhood::Moore, Topology::Bounded);
+        board.advance(1_000_000_001, life);
+        assert_eq!(board.generation(), 1_000_000_001);
+        assert_eq!(board.grid(), &blinker.transpose());
+    }
+
+    #[test]
+    fn toroidal() {
+        let glider = picture(".#...\n..#..\n###..\n.....\n.....");
+        let mut board = Board::new(glider.clone(), Neighborhood::Moore, Topology::Toroidal);
+        for _ in 0..20 {
+            board.step(life);
+        }
+        assert_eq!(board.grid(), &glider);
+
+        let mut board = Board::new(glider, Neighborhood::VonNeumann, Topology::Toroidal);
+        board.step(|_, neighbors: &[bool]| neighbors.iter().any(|&n| n));
+        assert_eq!(board.grid().iter().filter(|&&c| c).count(), 15);
+    }
+
+    #[test]
+    fn sparse() {
+        let glider = picture(".#.\n..#\n###");
+        let mut board = Sparse::from_grid(&glider, Neighborhood::Moore);
+        for _ in 0..8 {
+            board.step(life);
+        }
+        assert_eq!(board.bounds(), Some(((2, 2), (4, 4))));
+        assert_eq!(board.to_grid(), glider);
+        assert_eq!(board.find_cycle(50, life), None);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel() {
+        let soup = Grid::from_fn(37, 23, |(x, y)| (x * 7 + y * 13) % 5 < 2);
+        let mut serial = Board::new(soup.clone(), Neighborhood::Moore, Topology::Toroidal);
+        let mut parallel = Board::new(soup, Neighborhood::Moore, Topology::Toroidal);
+        for _ in 0..10 {
+            serial.step(life);
+            parallel.par_step(life);
+        }
+        assert_eq!(serial.grid(), parallel.grid());
+    }
+}
@@ -0,0 +1,317 @@
+//! Reads the block letters some puzzles draw as their answer.
+//!
+//! Two fonts show up across the years: a small one, 6 pixels high on a
+//! 5 pixel pitch, and a large one, 10 pixels high on an 8 pixel pitch. The
+//! font is picked from the height of the lit area, so the picture may carry
+//! any amount of blank margin.
+
+use crate::grid::Grid;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OcrError {
+    /// Nothing is lit.
+    Blank,
+    /// The lit area is neither 6 nor 10 pixels high.
+    UnknownFont { height: usize },
+    /// The glyph whose first lit column is `column` is not a known letter.
+    UnknownGlyph { index: usize, column: usize },
+}
+
+struct Font {
+    height: usize,
+    pitch: usize,
+    glyphs: &'static [(char, &'static [&'static str])],
+}
+
+const SMALL: Font = Font {
+    height: 6,
+    pitch: 5,
+    glyphs: &[
+        ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+        ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+        ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+        ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+        ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+        ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+        ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+        ('I', &[".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+        ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+        ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+        ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+        ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+        ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+        ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+        ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('Y', &["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+        ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+    ],
+};
+
+const LARGE: Font = Font {
+    height: 10,
+    pitch: 8,
+    glyphs: &[
+        (
+            'A',
+            &[
+                "..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'B',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#",
+                "#....#", "#####.",
+            ],
+        ),
+        (
+            'C',
+            &[
+                ".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+                "#....#", ".####.",
+            ],
+        ),
+        (
+            'E',
+            &[
+                "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+                "#.....", "######",
+            ],
+        ),
+        (
+            'F',
+            &[
+                "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+                "#.....", "#.....",
+            ],
+        ),
+        (
+            'G',
+            &[
+                ".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#",
+                "#...##", ".###.#",
+            ],
+        ),
+        (
+            'H',
+            &[
+                "#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'J',
+            &[
+                "...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.",
+                "#...#.", ".###..",
+            ],
+        ),
+        (
+            'K',
+            &[
+                "#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..",
+                "#...#.", "#....#",
+            ],
+        ),
+        (
+            'L',
+            &[
+                "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+                "#.....", "######",
+            ],
+        ),
+        (
+            'N',
+            &[
+                "#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##",
+                "#...##", "#....#",
+            ],
+        ),
+        (
+            'P',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....",
+                "#.....", "#.....",
+            ],
+        ),
+        (
+            'R',
+            &[
+                "#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'X',
+            &[
+                "#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.",
+                "#....#", "#....#",
+            ],
+        ),
+        (
+            'Z',
+            &[
+                "######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....",
+                "#.....", "######",
+            ],
+        ),
+    ],
+};
+
+/// Reads the letters drawn by the `true` cells of `grid`.
+pub fn read(grid: &Grid<bool>) -> Result<String, OcrError> {
+    read_with(grid, |&lit| lit)
+}
+
+/// Reads the letters drawn by the cells of `grid` for which `lit` holds.
+pub fn read_with<T, F>(grid: &Grid<T>, lit: F) -> Result<String, OcrError>
+where
+    F: Fn(&T) -> bool,
+{
+    let lit = grid.map(lit);
+    let lit_positions = || lit.enumerate().filter(|(_, &on)| on).map(|(pos, _)| pos);
+    let left = lit_positions()
+        .map(|(x, _)| x)
+        .min()
+        .ok_or(OcrError::Blank)?;
+    let right = lit_positions().map(|(x, _)| x).max().unwrap_or(left);
+    let top = lit_positions().map(|(_, y)| y).min().unwrap_or(0);
+    let bottom = lit_positions().map(|(_, y)| y).max().unwrap_or(top);
+
+    let height = bottom - top + 1;
+    let font = [SMALL, LARGE]
+        .into_iter()
+        .find(|font| font.height == height)
+        .ok_or(OcrError::UnknownFont { height })?;
+
+    let column_lit = |x| (top..=bottom).any(|y| lit[(x, y)]);
+    let mut letters = String::new();
+    let mut column = left;
+    while column <= right {
+        // A glyph may start with blank columns, like the small `I`, so each
+        // one is tried where its first lit column would line up.
+        let found = font.glyphs.iter().find_map(|&(letter, rows)| {
+            let lead = (0..font.pitch)
+                .find(|&x| rows.iter().any(|row| row.as_bytes().get(x) == Some(&b'#')))?;
+            let start = column.checked_sub(lead)?;
+            let matches = rows.iter().enumerate().all(|(y, row)| {
+                (0..font.pitch).all(|x| {
+                    let pixel = lit.get((start + x, top + y)).copied().unwrap_or(false);
+                    pixel == (row.as_bytes().get(x) == Some(&b'#'))
+                })
+            });
+            matches.then_some((letter, start))
+        });
+        let (letter, start) = found.ok_or(OcrError::UnknownGlyph {
+            index: letters.chars().count(),
+            column,
+        })?;
+        letters.push(letter);
+        column = start + font.pitch;
+        while column <= right && !column_lit(column) {
+            column += 1;
+        }
+    }
+    Ok(letters)
+}
+
+impl Display for OcrError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OcrError::Blank => write!(fmt, "the picture is blank"),
+            OcrError::UnknownFont { height } => {
+                write!(fmt, "no font draws letters {} pixels high", height)
+            }
+            OcrError::UnknownGlyph { index, column } => write!(
+                fmt,
+                "letter {} (column {}) is not a known glyph",
+                index + 1,
+                column
+            ),
+        }
+    }
+}
+
+impl Error for OcrError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picture(rows: &[&str]) -> Grid<bool> {
+        Grid::parse(&rows.join("\n"), |c| c == '#').unwrap()
+    }
+
+    #[test]
+    fn small() {
+        let grid = picture(&[
+            "...........................",
+            "..####.###...##..#..#.#...#",
+            "..#....#..#.#..#.#..#.#...#",
+            "..###..###..#....####..#.#.",
+            "..#....#..#.#....#..#...#..",
+            "..#....#..#.#..#.#..#...#..",
+            "..####.###...##..#..#...#..",
+        ]);
+        assert_eq!(read(&grid), Ok("EBCHY".to_owned()));
+    }
+
+    #[test]
+    fn leading_blank_column() {
+        let rows = [
+            ".###.####",
+            "..#..#...",
+            "..#..###.",
+            "..#..#...",
+            "..#..#...",
+            ".###.####",
+        ];
+        assert_eq!(read(&picture(&rows)), Ok("IE".to_owned()));
+        let rows = [
+            "####..###",
+            "#......#.",
+            "###....#.",
+            "#......#.",
+            "#......#.",
+            "####..###",
+        ];
+        assert_eq!(read(&picture(&rows)), Ok("EI".to_owned()));
+    }
+
+    #[test]
+    fn large() {
+        let rows = [
+            "#....#..#####.",
+            "##...#..#....#",
+            "##...#..#....#",
+            "#.#..#..#....#",
+            "#.#..#..#####.",
+            "#..#.#..#.....",
+            "#..#.#..#.....",
+            "#...##..#.....",
+            "#...##..#.....",
+            "#....#..#.....",
+        ];
+        assert_eq!(read(&picture(&rows)), Ok("NP".to_owned()));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(read(&picture(&["...."])), Err(OcrError::Blank));
+        assert_eq!(
+            read(&picture(&["#", "#"])),
+            Err(OcrError::UnknownFont { height: 2 })
+        );
+        let rows = ["####", "#..#", "#..#", "#..#", "#..#", "####"];
+        assert_eq!(
+            read(&picture(&rows)).unwrap_err().to_string(),
+            "letter 1 (column 0) is not a known glyph"
+        );
+    }
+}
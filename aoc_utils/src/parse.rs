@@ -0,0 +1,348 @@
+//! Parser combinators for the shapes puzzle inputs come in, built on `nom`.
+//!
+//! Every parser tolerates extra spaces and tabs between tokens, and
+//! [`Day::parse_with`] / [`Day::parse_lines`] turn a failure into an
+//! [`AocError`] pointing at the offending line.
+
+use crate::{
+    error::{AocError, Day},
+    grid::Grid,
+};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, digit1, line_ending, multispace0, none_of, one_of, space0},
+    combinator::{all_consuming, cut, map, map_res, opt, recognize},
+    error::{context, VerboseError, VerboseErrorKind},
+    multi::{many1, separated_list1},
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
+    Err, Parser,
+};
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+pub type IResult<'a, O> = nom::IResult<&'a str, O, VerboseError<&'a str>>;
+
+/// A minute-resolution point in time, as written in `[1518-11-01 00:00]`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+/// Surrounds `inner` with optional spaces and tabs.
+pub fn ws<'a, O, F>(inner: F) -> impl FnMut(&'a str) -> IResult<'a, O>
+where
+    F: Parser<&'a str, O, VerboseError<&'a str>>,
+{
+    delimited(space0, inner, space0)
+}
+
+/// An unsigned integer.
+pub fn unsigned<T: FromStr>(input: &str) -> IResult<'_, T> {
+    context("an unsigned integer", map_res(digit1, str::parse))(input)
+}
+
+/// An integer with an optional `+` or `-` sign.
+pub fn signed<T: FromStr>(input: &str) -> IResult<'_, T> {
+    context(
+        "an integer",
+        map_res(recognize(pair(opt(one_of("+-")), digit1)), |s: &str| {
+            s.trim_start_matches('+').parse()
+        }),
+    )(input)
+}
+
+/// One or more signed integers separated by `sep`.
+pub fn integer_list<'a, T: FromStr>(sep: &'a str) -> impl FnMut(&'a str) -> IResult<'a, Vec<T>> {
+    separated_list1(ws(tag(sep)), ws(signed))
+}
+
+/// Whitespace separated signed integers, possibly spanning several lines.
+pub fn integers<T: FromStr>(input: &str) -> IResult<'_, Vec<T>> {
+    many1(delimited(multispace0, signed, multispace0))(input)
+}
+
+/// Two signed integers separated by a comma, like `3, -4`.
+pub fn coordinates<T: FromStr>(input: &str) -> IResult<'_, (T, T)> {
+    context(
+        "a coordinate pair",
+        separated_pair(ws(signed), char(','), ws(signed)),
+    )(input)
+}
+
+/// `inner` wrapped in `open` and `close`, with optional padding inside.
+pub fn bracketed<'a, O, F>(
+    open: char,
+    inner: F,
+    close: char,
+) -> impl FnMut(&'a str) -> IResult<'a, O>
+where
+    F: Parser<&'a str, O, VerboseError<&'a str>>,
+{
+    delimited(char(open), ws(inner), char(close))
+}
+
+/// A `key => value` rule.
+pub fn rule<'a, K, V, F, G>(key: F, value: G) -> impl FnMut(&'a str) -> IResult<'a, (K, V)>
+where
+    F: Parser<&'a str, K, VerboseError<&'a str>>,
+    G: Parser<&'a str, V, VerboseError<&'a str>>,
+{
+    context(
+        "a `key => value` rule",
+        separated_pair(key, ws(tag("=>")), value),
+    )
+}
+
+/// A `[YYYY-MM-DD hh:mm]` timestamp.
+pub fn timestamp(input: &str) -> IResult<'_, Timestamp> {
+    let date = tuple((unsigned, char('-'), unsigned, char('-'), unsigned));
+    let time = separated_pair(unsigned, char(':'), unsigned);
+    context(
+        "a `[YYYY-MM-DD hh:mm]` timestamp",
+        map(
+            bracketed('[', separated_pair(date, space0, time), ']'),
+            |((year, _, month, _, day), (hour, minute))| Timestamp {
+                year,
+                month,
+                day,
+                hour,
+                minute,
+            },
+        ),
+    )(input)
+}
+
+/// A rectangular character map, mapping every cell with `f`. Lines may be
+/// shorter than the widest one, the gaps are filled with `f(' ')`.
+pub fn char_grid<'a, T, F>(f: F) -> impl FnMut(&'a str) -> IResult<'a, Grid<T>>
+where
+    T: Clone,
+    F: Fn(char) -> Option<T> + Copy,
+{
+    move |input| {
+        let cell = move |i| {
+            let (rest, c) = none_of("\r\n")(i)?;
+            match f(c) {
+                Some(cell) => Ok((rest, cell)),
+                None => Err(Err::Error(VerboseError {
+                    errors: vec![(i, VerboseErrorKind::Context("a known map cell"))],
+                })),
+            }
+        };
+        let (rest, rows) = separated_list1(line_ending, many1(cell))(input)?;
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let pad = f(' ');
+        let mut cells = Vec::with_capacity(width * rows.len());
+        for row in rows.iter() {
+            if row.len() < width && pad.is_none() {
+                let err = VerboseErrorKind::Context("a complete row");
+                return Err(Err::Failure(VerboseError {
+                    errors: vec![(input, err)],
+                }));
+            }
+            cells.extend(row.iter().cloned());
+            cells.extend(
+                std::iter::repeat(pad.clone())
+                    .flatten()
+                    .take(width - row.len()),
+            );
+        }
+        let grid = Grid::from_vec(width, rows.len(), cells).expect("rows are padded to width");
+        Ok((rest, grid))
+    }
+}
+
+/// A single word made of ASCII letters and digits.
+pub fn word(input: &str) -> IResult<'_, &str> {
+    context(
+        "a word",
+        nom::bytes::complete::take_while1(|c: char| c.is_ascii_alphanumeric()),
+    )(input)
+}
+
+/// `inner` followed by an optional line ending, for trailing newlines.
+pub fn line<'a, O, F>(inner: F) -> impl FnMut(&'a str) -> IResult<'a, O>
+where
+    F: Parser<&'a str, O, VerboseError<&'a str>>,
+{
+    terminated(inner, opt(line_ending))
+}
+
+/// One `inner` per line. A line that does not match is an error rather
+/// than the end of the list, which keeps error locations precise.
+pub fn lines_of<'a, O, F>(inner: F) -> impl FnMut(&'a str) -> IResult<'a, Vec<O>>
+where
+    F: Parser<&'a str, O, VerboseError<&'a str>>,
+{
+    separated_list1(line_ending, cut(inner))
+}
+
+/// Skips a literal `prefix` with optional surrounding padding.
+pub fn labeled<'a, O, F>(prefix: &'static str, inner: F) -> impl FnMut(&'a str) -> IResult<'a, O>
+where
+    F: Parser<&'a str, O, VerboseError<&'a str>>,
+{
+    preceded(ws(tag(prefix)), inner)
+}
+
+/// One of the given single-character alternatives.
+pub fn symbol<'a, T: Copy>(choices: &'a [(char, T)]) -> impl FnMut(&'a str) -> IResult<'a, T> {
+    move |input| {
+        for (c, value) in choices {
+            if let Ok((rest, _)) = char::<_, VerboseError<&str>>(*c)(input) {
+                return Ok((rest, *value));
+            }
+        }
+        let expected = VerboseErrorKind::Context("a known symbol");
+        Err(Err::Error(VerboseError {
+            errors: vec![(input, expected)],
+        }))
+    }
+}
+
+/// Either `#` (true) or `.` (false), the usual on/off cell notation.
+pub fn flag(input: &str) -> IResult<'_, bool> {
+    context(
+        "`#` or `.`",
+        alt((map(char('#'), |_| true), map(char('.'), |_| false))),
+    )(input)
+}
+
+impl Day {
+    /// Runs `parser` over the whole of `input`, ignoring surrounding blank
+    /// lines.
+    pub fn parse_with<'a, O, F>(self, input: &'a str, parser: F) -> Result<O, AocError>
+    where
+        F: Parser<&'a str, O, VerboseError<&'a str>>,
+    {
+        let trimmed = input.trim_matches(['\n', '\r']);
+        let offset = input.len() - input.trim_start_matches(['\n', '\r']).len();
+        all_consuming(parser)(trimmed).map(|(_, o)| o).map_err(|e| {
+            let (at, message) = describe(trimmed, e);
+            let line = input[..offset + at].matches('\n').count();
+            let text = input.lines().nth(line).unwrap_or("");
+            self.line_error(line, text, message)
+        })
+    }
+
+    /// Runs `parser` over every non-empty line of `input`.
+    pub fn parse_lines<'a, O, F>(self, input: &'a str, parser: F) -> Result<Vec<O>, AocError>
+    where
+        F: Parser<&'a str, O, VerboseError<&'a str>>,
+    {
+        let mut parser = all_consuming(ws(parser));
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                parser(line).map(|(_, o)| o).map_err(|e| {
+                    let (_, message) = describe(line, e);
+                    self.line_error(i, line, message)
+                })
+            })
+            .collect()
+    }
+}
+
+/// Finds the byte offset of the failure inside `input` and a message naming
+/// what was expected there.
+fn describe(input: &str, e: Err<VerboseError<&str>>) -> (usize, String) {
+    let errors = match e {
+        Err::Error(e) | Err::Failure(e) => e.errors,
+        Err::Incomplete(_) => return (input.len(), "unexpected end of input".to_owned()),
+    };
+    let (rest, _) = errors
+        .first()
+        .cloned()
+        .unwrap_or(("", VerboseErrorKind::Context("")));
+    let at = input.len() - rest.len();
+    let column = at - input[..at].rfind('\n').map_or(0, |i| i + 1) + 1;
+    let expected = errors.iter().find_map(|(_, kind)| match kind {
+        VerboseErrorKind::Context(c) => Some(c.to_string()),
+        VerboseErrorKind::Char(c) => Some(format!("`{}`", c)),
+        VerboseErrorKind::Nom(nom::error::ErrorKind::Eof) => Some("end of input".to_owned()),
+        VerboseErrorKind::Nom(nom::error::ErrorKind::Tag) => None,
+        VerboseErrorKind::Nom(_) => None,
+    });
+    let found = rest.chars().next().map_or_else(
+        || "end of input".to_owned(),
+        |c| format!("`{}`", c.escape_default()),
+    );
+    let message = match expected {
+        Some(expected) => format!(
+            "expected {} at column {}, found {}",
+            expected, column, found
+        ),
+        None => format!("unexpected {} at column {}", found, column),
+    };
+    (at, message)
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "{:04}-{:02}-{:02} {:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Day = Day::new(2018, 10);
+
+    #[test]
+    fn primitives() {
+        assert_eq!(signed::<i32>("+12"), Ok(("", 12)));
+        assert_eq!(signed::<i32>("-3x"), Ok(("x", -3)));
+        assert_eq!(
+            integer_list::<i8>(",")("1, -2 ,3"),
+            Ok(("", vec![1, -2, 3]))
+        );
+        assert_eq!(
+            bracketed('<', coordinates, '>')("< 3,-4>"),
+            Ok(("", (3, -4)))
+        );
+        assert_eq!(
+            rule(many1(flag), flag)("..#.# => #"),
+            Ok(("", (vec![false, false, true, false, true], true)))
+        );
+        let (_, ts) = timestamp("[1518-11-01 00:05]").unwrap();
+        assert_eq!((ts.year, ts.month, ts.minute), (1518, 11, 5));
+        assert_eq!(ts.to_string(), "1518-11-01 00:05");
+        let (_, grid) = char_grid(|c| Some(c == '#'))("#.\n.#\n#").unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert!(!grid[(1, 2)]);
+    }
+
+    #[test]
+    fn errors() {
+        let star = tuple((
+            labeled("position=", bracketed('<', coordinates::<i32>, '>')),
+            labeled("velocity=", bracketed('<', coordinates::<i32>, '>')),
+        ));
+        let input = "position=< 9,  1> velocity=< 0,  2>\nposition=< 7, 0> velocity=< -1,  x>\n";
+        let err = DAY.parse_lines(input, star).unwrap_err();
+        assert_eq!(err.location.as_ref().unwrap().0, 2);
+        assert_eq!(err.message, "expected an integer at column 34, found `x`");
+
+        let err = DAY
+            .parse_with("\n1,2\n3,y\n", lines_of(coordinates::<u8>))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "2018 day 10, line 3: expected an integer at column 3, found `y` (in `3,y`)"
+        );
+    }
+}
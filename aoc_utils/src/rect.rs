@@ -0,0 +1,311 @@
+//! Axis-aligned rectangles on the integer grid, and questions about how a
+//! whole set of them overlaps.
+//!
+//! Areas come from a sweep line over the rectangles' edges rather than from
+//! visiting every cell, so they cost O(n log n) however large the rectangles
+//! are.
+
+use crate::grid::{Grid, Pos};
+
+/// The cells `x..x + width` by `y..y + height`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// One past the rightmost column.
+    pub fn right(&self) -> usize {
+        self.x + self.width
+    }
+
+    /// One past the bottom row.
+    pub fn bottom(&self) -> usize {
+        self.y + self.height
+    }
+
+    pub fn area(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.area() == 0
+    }
+
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        (self.x..self.right()).contains(&x) && (self.y..self.bottom()).contains(&y)
+    }
+
+    /// The cells in both rectangles, if there are any.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        let (right, bottom) = (
+            self.right().min(other.right()),
+            self.bottom().min(other.bottom()),
+        );
+        (x < right && y < bottom).then(|| Rect::new(x, y, right - x, bottom - y))
+    }
+
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Every cell of the rectangle, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = Pos> {
+        let (xs, ys) = (self.x..self.right(), self.y..self.bottom());
+        ys.flat_map(move |y| xs.clone().map(move |x| (x, y)))
+    }
+}
+
+/// The number of cells inside at least one of `rects`.
+pub fn union_area(rects: &[Rect]) -> usize {
+    covered_area(rects, 1)
+}
+
+/// The number of cells inside at least `k` of `rects` (a `k` of 0 counts as
+/// 1).
+pub fn covered_area(rects: &[Rect], k: usize) -> usize {
+    let k = k.max(1);
+    let mut ys: Vec<usize> = rects.iter().flat_map(|r| [r.y, r.bottom()]).collect();
+    ys.sort_unstable();
+    ys.dedup();
+    let band = |y: usize| ys.binary_search(&y).unwrap();
+
+    let mut edges: Vec<(usize, isize, &Rect)> = rects
+        .iter()
+        .filter(|r| !r.is_empty())
+        .flat_map(|r| [(r.x, 1, r), (r.right(), -1, r)])
+        .collect();
+    edges.sort_unstable_by_key(|&(x, _, _)| x);
+
+    let mut cover = Cover::new(&ys, k);
+    let mut area = 0;
+    let mut last_x = 0;
+    for (x, delta, rect) in edges {
+        area += (x - last_x) * cover.at_least(k);
+        last_x = x;
+        cover.add(band(rect.y), band(rect.bottom()), delta);
+    }
+    area
+}
+
+/// A segment tree over the bands between consecutive `ys`, tracking how
+/// much of the column is covered at least `j` times for every `j <= k`.
+struct Cover<'a> {
+    ys: &'a [usize],
+    k: usize,
+    counts: Vec<isize>,
+    /// `lengths[node * (k + 1) + j]` is how much of the node's span is
+    /// covered at least `j` times by intervals added at or below it.
+    lengths: Vec<usize>,
+}
+
+impl<'a> Cover<'a> {
+    fn new(ys: &'a [usize], k: usize) -> Self {
+        let nodes = 4 * ys.len().max(1);
+        let mut cover = Cover {
+            ys,
+            k,
+            counts: vec![0; nodes],
+            lengths: vec![0; nodes * (k + 1)],
+        };
+        if ys.len() > 1 {
+            cover.build(1, 0, ys.len() - 1);
+        }
+        cover
+    }
+
+    fn build(&mut self, node: usize, lo: usize, hi: usize) {
+        if hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            self.build(2 * node, lo, mid);
+            self.build(2 * node + 1, mid, hi);
+        }
+        self.refresh(node, lo, hi);
+    }
+
+    fn at_least(&self, j: usize) -> usize {
+        if self.ys.len() > 1 {
+            self.lengths[self.k + 1 + j]
+        } else {
+            0
+        }
+    }
+
+    /// Adds `delta` to the count of bands `from..to`.
+    fn add(&mut self, from: usize, to: usize, delta: isize) {
+        self.update(1, 0, self.ys.len() - 1, from, to, delta);
+    }
+
+    fn update(&mut self, node: usize, lo: usize, hi: usize, from: usize, to: usize, delta: isize) {
+        if to <= lo || hi <= from {
+            return;
+        }
+        if from <= lo && hi <= to {
+            self.counts[node] += delta;
+        } else {
+            let mid = (lo + hi) / 2;
+            self.update(2 * node, lo, mid, from, to, delta);
+            self.update(2 * node + 1, mid, hi, from, to, delta);
+        }
+        self.refresh(node, lo, hi);
+    }
+
+    fn refresh(&mut self, node: usize, lo: usize, hi: usize) {
+        let count = self.counts[node] as usize;
+        let full = self.ys[hi] - self.ys[lo];
+        let leaf = hi - lo == 1;
+        for j in 0..=self.k {
+            self.lengths[node * (self.k + 1) + j] = if count >= j {
+                full
+            } else if leaf {
+                0
+            } else {
+                let below = j - count;
+                self.lengths[2 * node * (self.k + 1) + below]
+                    + self.lengths[(2 * node + 1) * (self.k + 1) + below]
+            };
+        }
+    }
+}
+
+/// Which of a set of rectangles overlap each other.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Overlaps {
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Overlaps {
+    /// Finds every overlapping pair by sweeping left to right, so only
+    /// rectangles that share some column are ever compared.
+    pub fn new(rects: &[Rect]) -> Self {
+        let mut neighbors = vec![Vec::new(); rects.len()];
+        let mut order: Vec<usize> = (0..rects.len()).filter(|&i| !rects[i].is_empty()).collect();
+        order.sort_unstable_by_key(|&i| rects[i].x);
+
+        let mut active: Vec<usize> = Vec::new();
+        for i in order {
+            let rect = &rects[i];
+            active.retain(|&j| rects[j].right() > rect.x);
+            for &j in &active {
+                if rect.overlaps(&rects[j]) {
+                    neighbors[i].push(j);
+                    neighbors[j].push(i);
+                }
+            }
+            active.push(i);
+        }
+        for list in &mut neighbors {
+            list.sort_unstable();
+        }
+        Overlaps { neighbors }
+    }
+
+    /// The indices of the rectangles overlapping rectangle `i`, in order.
+    pub fn of(&self, i: usize) -> &[usize] {
+        &self.neighbors[i]
+    }
+
+    /// How many rectangles overlap rectangle `i`.
+    pub fn count(&self, i: usize) -> usize {
+        self.neighbors[i].len()
+    }
+
+    /// The indices of the rectangles that overlap nothing.
+    pub fn isolated(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.neighbors.len()).filter(move |&i| self.neighbors[i].is_empty())
+    }
+}
+
+/// How many of `rects` cover each cell, from `(0, 0)` to the furthest
+/// rectangle edge.
+pub fn coverage(rects: &[Rect]) -> Grid<usize> {
+    let width = rects.iter().map(Rect::right).max().unwrap_or(0);
+    let height = rects.iter().map(Rect::bottom).max().unwrap_or(0);
+    let mut deltas = Grid::new(width + 1, height + 1, 0isize);
+    for r in rects.iter().filter(|r| !r.is_empty()) {
+        deltas[(r.x, r.y)] += 1;
+        deltas[(r.right(), r.y)] -= 1;
+        deltas[(r.x, r.bottom())] -= 1;
+        deltas[(r.right(), r.bottom())] += 1;
+    }
+    Grid::from_fn(width, height, |(x, y)| {
+        let above = if y > 0 { deltas[(x, y - 1)] } else { 0 };
+        let left = if x > 0 { deltas[(x - 1, y)] } else { 0 };
+        let corner = if x > 0 && y > 0 {
+            deltas[(x - 1, y - 1)]
+        } else {
+            0
+        };
+        deltas[(x, y)] += above + left - corner;
+        deltas[(x, y)] as usize
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<Rect> {
+        vec![
+            Rect::new(1, 3, 4, 4),
+            Rect::new(3, 1, 4, 4),
+            Rect::new(5, 5, 2, 2),
+        ]
+    }
+
+    #[test]
+    fn rects() {
+        let [a, b, c] = <[Rect; 3]>::try_from(example()).unwrap();
+        assert_eq!(a.intersection(&b), Some(Rect::new(3, 3, 2, 2)));
+        assert_eq!(b.intersection(&c), None);
+        assert!(!a.overlaps(&c));
+        assert!(a.contains((4, 6)) && !a.contains((5, 6)));
+        assert_eq!(
+            c.cells().collect::<Vec<_>>(),
+            [(5, 5), (6, 5), (5, 6), (6, 6)]
+        );
+    }
+
+    #[test]
+    fn areas() {
+        let rects = example();
+        assert_eq!(union_area(&rects), 16 + 16 - 4 + 4);
+        assert_eq!(covered_area(&rects, 2), 4);
+        assert_eq!(covered_area(&rects, 3), 0);
+        assert_eq!(union_area(&[]), 0);
+
+        let stacked = [
+            Rect::new(0, 0, 3, 3),
+            Rect::new(1, 1, 3, 3),
+            Rect::new(2, 2, 3, 3),
+        ];
+        let grid = coverage(&stacked);
+        for k in 1..=3 {
+            let cells = grid.iter().filter(|&&n| n >= k).count();
+            assert_eq!(covered_area(&stacked, k), cells);
+        }
+        assert_eq!(grid[(2, 2)], 3);
+        assert_eq!(grid[(4, 0)], 0);
+    }
+
+    #[test]
+    fn overlaps() {
+        let overlaps = Overlaps::new(&example());
+        assert_eq!(overlaps.of(0), [1]);
+        assert_eq!(overlaps.count(1), 1);
+        assert_eq!(overlaps.isolated().collect::<Vec<_>>(), [2]);
+    }
+}
@@ -0,0 +1,162 @@
+//! A circular list with a cursor, for games played around a circle.
+//!
+//! Nodes live in an arena and link to each other by index, so inserting or
+//! removing at the cursor is O(1) and moving the cursor by `k` is O(k).
+
+/// A circle of values with a cursor on one of them. Clockwise is "right".
+#[derive(Clone, Debug, Default)]
+pub struct Ring<T> {
+    values: Vec<Option<T>>,
+    next: Vec<usize>,
+    prev: Vec<usize>,
+    free: Vec<usize>,
+    cursor: Option<usize>,
+    len: usize,
+}
+
+impl<T> Ring<T> {
+    pub fn new() -> Self {
+        Ring {
+            values: Vec::new(),
+            next: Vec::new(),
+            prev: Vec::new(),
+            free: Vec::new(),
+            cursor: None,
+            len: 0,
+        }
+    }
+
+    /// An empty ring with room for `capacity` values before reallocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Ring {
+            values: Vec::with_capacity(capacity),
+            next: Vec::with_capacity(capacity),
+            prev: Vec::with_capacity(capacity),
+            ..Ring::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The value under the cursor.
+    pub fn current(&self) -> Option<&T> {
+        self.cursor.and_then(|i| self.values[i].as_ref())
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut T> {
+        self.cursor.and_then(move |i| self.values[i].as_mut())
+    }
+
+    /// Moves the cursor `k` values clockwise.
+    pub fn rotate_right(&mut self, k: usize) {
+        if let Some(mut i) = self.cursor {
+            for _ in 0..k % self.len {
+                i = self.next[i];
+            }
+            self.cursor = Some(i);
+        }
+    }
+
+    /// Moves the cursor `k` values counter-clockwise.
+    pub fn rotate_left(&mut self, k: usize) {
+        if let Some(mut i) = self.cursor {
+            for _ in 0..k % self.len {
+                i = self.prev[i];
+            }
+            self.cursor = Some(i);
+        }
+    }
+
+    /// Inserts `value` clockwise of the cursor and moves the cursor onto it.
+    pub fn insert(&mut self, value: T) {
+        let node = match self.free.pop() {
+            Some(node) => {
+                self.values[node] = Some(value);
+                node
+            }
+            None => {
+                self.values.push(Some(value));
+                self.next.push(0);
+                self.prev.push(0);
+                self.values.len() - 1
+            }
+        };
+        let (before, after) = match self.cursor {
+            Some(i) => (i, self.next[i]),
+            None => (node, node),
+        };
+        self.next[before] = node;
+        self.prev[after] = node;
+        self.prev[node] = before;
+        self.next[node] = after;
+        self.cursor = Some(node);
+        self.len += 1;
+    }
+
+    /// Removes the value under the cursor, moving the cursor clockwise.
+    pub fn remove(&mut self) -> Option<T> {
+        let node = self.cursor?;
+        let (before, after) = (self.prev[node], self.next[node]);
+        self.next[before] = after;
+        self.prev[after] = before;
+        self.cursor = if after == node { None } else { Some(after) };
+        self.free.push(node);
+        self.len -= 1;
+        self.values[node].take()
+    }
+
+    /// The values clockwise from the cursor.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        let mut node = self.cursor;
+        (0..self.len).filter_map(move |_| {
+            let i = node?;
+            node = Some(self.next[i]);
+            self.values[i].as_ref()
+        })
+    }
+}
+
+impl<T> FromIterator<T> for Ring<T> {
+    /// A ring holding the values in clockwise order, with the cursor on the
+    /// last one.
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let mut ring = Ring::new();
+        for value in values {
+            ring.insert(value);
+        }
+        ring
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring() {
+        let mut ring: Ring<_> = (0..5).collect();
+        assert_eq!(ring.current(), Some(&4));
+        ring.rotate_right(2);
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 0]);
+        ring.rotate_left(13);
+        assert_eq!(ring.current(), Some(&3));
+        assert_eq!(ring.remove(), Some(3));
+        assert_eq!(ring.current(), Some(&4));
+        ring.insert(7);
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), [7, 0, 1, 2, 4]);
+        assert_eq!(ring.len(), 5);
+
+        while ring.remove().is_some() {}
+        assert!(ring.is_empty());
+        assert_eq!(ring.current(), None);
+        ring.rotate_right(3);
+        ring.insert(9);
+        assert_eq!(ring.iter().collect::<Vec<_>>(), [&9]);
+    }
+}
@@ -0,0 +1,213 @@
+//! Runs dependent tasks on a pool of workers, one second at a time.
+//!
+//! Whenever a worker is free it takes the smallest task whose requirements
+//! are all finished, and free workers are filled lowest index first, so a
+//! schedule is fully determined by its input.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    error::Error,
+    fmt::{self, Display},
+    hash::Hash,
+};
+
+/// Who worked on what during each second of a schedule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Timeline<N> {
+    pub workers: usize,
+    /// `seconds[t][w]` is the task worker `w` spent second `t` on.
+    pub seconds: Vec<Vec<Option<N>>>,
+    /// Every task with the second it was finished by, in finishing order.
+    pub finished: Vec<(usize, N)>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// There is no one to do the work.
+    NoWorkers,
+    /// The requirements contain a cycle, so some tasks can never start.
+    Cycle,
+}
+
+/// Runs every task mentioned in `tasks` or `requirements` on `workers`
+/// workers, where `(a, b)` means `a` must finish before `b` starts and a task
+/// takes `duration(task)` seconds (at least one).
+pub fn schedule<N, T, R, F>(
+    tasks: T,
+    requirements: R,
+    workers: usize,
+    duration: F,
+) -> Result<Timeline<N>, ScheduleError>
+where
+    N: Clone + Ord + Hash,
+    T: IntoIterator<Item = N>,
+    R: IntoIterator<Item = (N, N)>,
+    F: Fn(&N) -> usize,
+{
+    if workers == 0 {
+        return Err(ScheduleError::NoWorkers);
+    }
+    let mut waiting_on: HashMap<N, usize> = tasks.into_iter().map(|t| (t, 0)).collect();
+    let mut dependents: HashMap<N, Vec<N>> = HashMap::new();
+    for (before, after) in requirements {
+        waiting_on.entry(before.clone()).or_insert(0);
+        *waiting_on.entry(after.clone()).or_insert(0) += 1;
+        dependents.entry(before).or_default().push(after);
+    }
+    let total = waiting_on.len();
+    let mut ready: BTreeSet<N> = waiting_on
+        .iter()
+        .filter(|(_, &count)| count == 0)
+        .map(|(task, _)| task.clone())
+        .collect();
+
+    let mut busy: Vec<Option<(N, usize)>> = vec![None; workers];
+    let mut timeline = Timeline {
+        workers,
+        seconds: Vec::new(),
+        finished: Vec::new(),
+    };
+    loop {
+        for worker in busy.iter_mut().filter(|w| w.is_none()) {
+            match ready.pop_first() {
+                Some(task) => {
+                    let time = duration(&task).max(1);
+                    *worker = Some((task, time));
+                }
+                None => break,
+            }
+        }
+        if busy.iter().all(Option::is_none) {
+            break;
+        }
+        timeline.seconds.push(
+            busy.iter()
+                .map(|w| w.as_ref().map(|(task, _)| task.clone()))
+                .collect(),
+        );
+
+        let now = timeline.seconds.len();
+        let mut done = Vec::new();
+        for worker in busy.iter_mut() {
+            if let Some((_, left)) = worker {
+                *left -= 1;
+                if *left == 0 {
+                    done.push(worker.take().unwrap().0);
+                }
+            }
+        }
+        done.sort();
+        for task in done {
+            for next in dependents.get(&task).into_iter().flatten() {
+                let count = waiting_on.get_mut(next).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.insert(next.clone());
+                }
+            }
+            timeline.finished.push((now, task));
+        }
+    }
+    if timeline.finished.len() < total {
+        return Err(ScheduleError::Cycle);
+    }
+    Ok(timeline)
+}
+
+impl<N> Timeline<N> {
+    /// The number of seconds until every task is finished.
+    pub fn duration(&self) -> usize {
+        self.seconds.len()
+    }
+
+    /// The tasks in the order they were finished.
+    pub fn order(&self) -> impl Iterator<Item = &N> {
+        self.finished.iter().map(|(_, task)| task)
+    }
+}
+
+impl<N: Display> Display for Timeline<N> {
+    /// The table from the puzzle text: one row per second, showing what each
+    /// worker is doing (`.` when idle) and the tasks finished so far, ending
+    /// with the first second everyone is idle.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let mut line = String::from("Second");
+        for w in 1..=self.workers {
+            line += &format!("   Worker {}", w);
+        }
+        writeln!(fmt, "{}   Done", line)?;
+
+        let mut done = String::new();
+        let mut finished = self.finished.iter().peekable();
+        let idle: Vec<Option<N>> = (0..self.workers).map(|_| None).collect();
+        let rows = self.seconds.iter().chain(Some(&idle));
+        for (second, row) in rows.enumerate() {
+            while let Some((_, task)) = finished.next_if(|(t, _)| *t <= second) {
+                done += &task.to_string();
+            }
+            let mut line = format!("{:>4}  ", second);
+            for task in row {
+                let name = task.as_ref().map_or(".".to_owned(), |t| t.to_string());
+                line += &format!("   {:^8}", name);
+            }
+            writeln!(fmt, "{}", format!("{}   {}", line, done).trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ScheduleError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScheduleError::NoWorkers => write!(fmt, "there are no workers"),
+            ScheduleError::Cycle => write!(fmt, "the requirements contain a cycle"),
+        }
+    }
+}
+
+impl Error for ScheduleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [(char, char); 7] = [
+        ('C', 'A'),
+        ('C', 'F'),
+        ('A', 'B'),
+        ('A', 'D'),
+        ('B', 'E'),
+        ('D', 'E'),
+        ('F', 'E'),
+    ];
+
+    #[test]
+    fn example() {
+        let duration = |&c: &char| (c as u8 - b'A' + 1) as usize;
+        let timeline = schedule(None, EXAMPLE, 2, duration).unwrap();
+        assert_eq!(timeline.duration(), 15);
+        assert_eq!(timeline.order().collect::<String>(), "CABFDE");
+        let table = timeline.to_string();
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines[0], "Second   Worker 1   Worker 2   Done");
+        assert_eq!(lines[1], "   0        C          .");
+        assert_eq!(lines[4], "   3        A          F       C");
+        assert_eq!(lines[15], "  14        E          .       CABFD");
+        assert_eq!(lines[16], "  15        .          .       CABFDE");
+    }
+
+    #[test]
+    fn edge_cases() {
+        let timeline = schedule(vec!['X'], EXAMPLE, 1, |_| 1).unwrap();
+        assert_eq!(timeline.order().collect::<String>(), "CABDFEX");
+        assert_eq!(
+            schedule(None, [(1, 2), (2, 1)], 3, |_| 1),
+            Err(ScheduleError::Cycle)
+        );
+        assert_eq!(
+            schedule(None, EXAMPLE, 0, |_| 1),
+            Err(ScheduleError::NoWorkers)
+        );
+        assert_eq!(schedule(None::<u8>, [], 2, |_| 1).unwrap().duration(), 0);
+    }
+}
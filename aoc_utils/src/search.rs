@@ -0,0 +1,496 @@
+//! Generic graph searches. Graphs are described either explicitly with a
+//! [`Graph`] or implicitly by a closure returning the neighbors of a node,
+//! which makes grid cells or puzzle states searchable without building the
+//! whole graph up front.
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// An explicit directed graph stored as adjacency lists.
+#[derive(Clone, Debug)]
+pub struct Graph<N> {
+    edges: HashMap<N, Vec<N>>,
+}
+
+impl<N: Clone + Eq + Hash> Graph<N> {
+    pub fn new() -> Self {
+        Self {
+            edges: HashMap::new(),
+        }
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        self.edges.entry(node).or_default();
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N) {
+        self.add_node(to.clone());
+        self.edges.entry(from).or_default().push(to);
+    }
+
+    /// Adds edges in both directions.
+    pub fn add_undirected_edge(&mut self, a: N, b: N) {
+        self.add_edge(a.clone(), b.clone());
+        self.add_edge(b, a);
+    }
+
+    pub fn successors(&self, node: &N) -> &[N] {
+        self.edges.get(node).map_or(&[], |v| &v[..])
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.edges.keys()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (&N, &N)> {
+        self.edges
+            .iter()
+            .flat_map(|(from, to)| to.iter().map(move |to| (from, to)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+}
+
+impl<N: Clone + Eq + Hash> Default for Graph<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Clone + Eq + Hash> FromIterator<(N, N)> for Graph<N> {
+    fn from_iter<T: IntoIterator<Item = (N, N)>>(iter: T) -> Self {
+        let mut graph = Graph::new();
+        for (from, to) in iter {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+}
+
+/// Breadth first traversal yielding every reachable node with its distance
+/// from `start`.
+pub fn bfs<N, F, I>(start: N, neighbors: F) -> Bfs<N, F>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    let mut seen = HashSet::new();
+    seen.insert(start.clone());
+    Bfs {
+        queue: VecDeque::from(vec![(start, 0)]),
+        seen,
+        neighbors,
+    }
+}
+
+pub struct Bfs<N, F> {
+    queue: VecDeque<(N, usize)>,
+    seen: HashSet<N>,
+    neighbors: F,
+}
+
+impl<N, F, I> Iterator for Bfs<N, F>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    type Item = (N, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, depth) = self.queue.pop_front()?;
+        for next in (self.neighbors)(&node) {
+            if self.seen.insert(next.clone()) {
+                self.queue.push_back((next, depth + 1));
+            }
+        }
+        Some((node, depth))
+    }
+}
+
+/// Depth first traversal in pre-order, yielding every reachable node once.
+pub fn dfs<N, F, I>(start: N, neighbors: F) -> Dfs<N, F>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    Dfs {
+        stack: vec![start],
+        seen: HashSet::new(),
+        neighbors,
+    }
+}
+
+pub struct Dfs<N, F> {
+    stack: Vec<N>,
+    seen: HashSet<N>,
+    neighbors: F,
+}
+
+impl<N, F, I> Iterator for Dfs<N, F>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    type Item = N;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = self.stack.pop()?;
+            if !self.seen.insert(node.clone()) {
+                continue;
+            }
+            let start = self.stack.len();
+            self.stack.extend(
+                (self.neighbors)(&node)
+                    .into_iter()
+                    .filter(|n| !self.seen.contains(n)),
+            );
+            // Visit neighbors in the order they were produced.
+            self.stack[start..].reverse();
+            return Some(node);
+        }
+    }
+}
+
+/// The shortest path, counted in edges, from `start` to the first node
+/// satisfying `goal`. The path includes both ends.
+pub fn bfs_path<N, F, I, G>(start: N, mut neighbors: F, mut goal: G) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+    G: FnMut(&N) -> bool,
+{
+    let mut parents: HashMap<N, Option<N>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    parents.insert(start.clone(), None);
+    queue.push_back(start);
+    while let Some(node) = queue.pop_front() {
+        if goal(&node) {
+            return Some(walk_back(&parents, node));
+        }
+        for next in neighbors(&node) {
+            if let Entry::Vacant(e) = parents.entry(next.clone()) {
+                e.insert(Some(node.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// The cheapest path from `start` to the first node satisfying `goal`,
+/// together with its total cost.
+pub fn dijkstra<N, C, F, I, G>(start: N, neighbors: F, goal: G) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    G: FnMut(&N) -> bool,
+{
+    astar(start, neighbors, |_| C::default(), goal)
+}
+
+/// The cost of the cheapest path from `start` to every reachable node.
+pub fn dijkstra_all<N, C, F, I>(start: N, mut neighbors: F) -> HashMap<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut costs = HashMap::new();
+    let mut done = HashSet::new();
+    let mut heap = BinaryHeap::new();
+    costs.insert(start.clone(), C::default());
+    heap.push(State {
+        priority: C::default(),
+        cost: C::default(),
+        node: start,
+    });
+    while let Some(State { cost, node, .. }) = heap.pop() {
+        if !done.insert(node.clone()) {
+            continue;
+        }
+        for (next, step) in neighbors(&node) {
+            let next_cost = cost + step;
+            if costs.get(&next).is_none_or(|c| next_cost < *c) {
+                costs.insert(next.clone(), next_cost);
+                heap.push(State {
+                    priority: next_cost,
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+    costs
+}
+
+/// A* search. `heuristic` must never overestimate the remaining cost for
+/// the returned path to be the cheapest one.
+pub fn astar<N, C, F, I, H, G>(
+    start: N,
+    mut neighbors: F,
+    mut heuristic: H,
+    mut goal: G,
+) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    H: FnMut(&N) -> C,
+    G: FnMut(&N) -> bool,
+{
+    let mut best: HashMap<N, (C, Option<N>)> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    best.insert(start.clone(), (C::default(), None));
+    heap.push(State {
+        priority: heuristic(&start),
+        cost: C::default(),
+        node: start,
+    });
+    while let Some(State { cost, node, .. }) = heap.pop() {
+        if best.get(&node).is_some_and(|(c, _)| cost > *c) {
+            continue;
+        }
+        if goal(&node) {
+            let parents: HashMap<N, Option<N>> = best
+                .into_iter()
+                .map(|(node, (_, parent))| (node, parent))
+                .collect();
+            return Some((walk_back(&parents, node), cost));
+        }
+        for (next, step) in neighbors(&node) {
+            let next_cost = cost + step;
+            if best.get(&next).is_none_or(|(c, _)| next_cost < *c) {
+                best.insert(next.clone(), (next_cost, Some(node.clone())));
+                heap.push(State {
+                    priority: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Orders the nodes so every edge points forward, always picking the
+/// smallest available node next (Kahn's algorithm with a min-heap).
+/// Returns `None` if the edges contain a cycle.
+pub fn topological_sort<N, I, E>(nodes: I, edges: E) -> Option<Vec<N>>
+where
+    N: Clone + Ord + Hash,
+    I: IntoIterator<Item = N>,
+    E: IntoIterator<Item = (N, N)>,
+{
+    let mut in_degree: HashMap<N, usize> = nodes.into_iter().map(|n| (n, 0)).collect();
+    let mut successors: HashMap<N, Vec<N>> = HashMap::new();
+    for (from, to) in edges {
+        in_degree.entry(from.clone()).or_insert(0);
+        *in_degree.entry(to.clone()).or_insert(0) += 1;
+        successors.entry(from).or_default().push(to);
+    }
+    let mut ready: BinaryHeap<Reverse<N>> = in_degree
+        .iter()
+        .filter(|(_, d)| **d == 0)
+        .map(|(n, _)| Reverse(n.clone()))
+        .collect();
+    let mut order = Vec::with_capacity(in_degree.len());
+    while let Some(Reverse(node)) = ready.pop() {
+        for next in successors.get(&node).into_iter().flatten() {
+            let degree = in_degree.get_mut(next).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.push(Reverse(next.clone()));
+            }
+        }
+        order.push(node);
+    }
+    if order.len() == in_degree.len() {
+        Some(order)
+    } else {
+        None
+    }
+}
+
+/// The closest common ancestor of `a` and `b` in a forest described by
+/// `parent`, with the number of steps from `a` and from `b` to reach it.
+pub fn lowest_common_ancestor<N, P>(a: N, b: N, mut parent: P) -> Option<(N, usize, usize)>
+where
+    N: Clone + Eq + Hash,
+    P: FnMut(&N) -> Option<N>,
+{
+    let mut ancestors = HashMap::new();
+    let mut node = Some(a);
+    let mut steps = 0;
+    while let Some(n) = node {
+        node = parent(&n);
+        if ancestors.insert(n, steps).is_some() {
+            break;
+        }
+        steps += 1;
+    }
+    let mut node = Some(b);
+    let mut seen = HashSet::new();
+    let mut steps = 0;
+    while let Some(n) = node {
+        if let Some(a_steps) = ancestors.get(&n) {
+            return Some((n, *a_steps, steps));
+        }
+        if !seen.insert(n.clone()) {
+            return None;
+        }
+        node = parent(&n);
+        steps += 1;
+    }
+    None
+}
+
+/// Partitions `nodes` into sets of mutually reachable nodes, treating every
+/// edge as undirected. `neighbors` must be symmetric.
+pub fn connected_components<N, F, I, J>(nodes: J, mut neighbors: F) -> Vec<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+    J: IntoIterator<Item = N>,
+{
+    let mut seen = HashSet::new();
+    let mut components = Vec::new();
+    for node in nodes {
+        if seen.contains(&node) {
+            continue;
+        }
+        let component: Vec<N> = bfs(node, &mut neighbors).map(|(n, _)| n).collect();
+        seen.extend(component.iter().cloned());
+        components.push(component);
+    }
+    components
+}
+
+fn walk_back<N: Clone + Eq + Hash>(parents: &HashMap<N, Option<N>>, end: N) -> Vec<N> {
+    let mut path = vec![end];
+    while let Some(Some(parent)) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+struct State<N, C> {
+    priority: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> Ord for State<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed to turn `BinaryHeap` into a min-heap.
+        other.priority.cmp(&self.priority)
+    }
+}
+
+impl<N, C: Ord> PartialOrd for State<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> PartialEq for State<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N, C: Ord> Eq for State<N, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    const MAZE: &str = "\
+.....
+.###.
+...#.
+##.#.
+.....
+";
+
+    #[test]
+    fn grid_paths() {
+        let maze = Grid::parse(MAZE, |c| c == '#').unwrap();
+        let open = |p: &(usize, usize)| -> Vec<(usize, usize)> {
+            maze.neighbors4(*p).filter(|n| !maze[*n]).collect()
+        };
+        let path = bfs_path((0, 0), open, |p| *p == (2, 3)).unwrap();
+        assert_eq!(path.len(), 6);
+        let (_, depth) = bfs((0, 0), open).find(|(p, _)| *p == (0, 4)).unwrap();
+        assert_eq!(depth, 8);
+        let weighted = |p: &(usize, usize)| open(p).into_iter().map(|n| (n, 1));
+        let manhattan = |p: &(usize, usize)| 4 - p.0 + 4 - p.1;
+        let (path, cost) = astar((0, 0), weighted, manhattan, |p| *p == (4, 4)).unwrap();
+        assert_eq!((path.len(), cost), (9, 8));
+        assert_eq!(dijkstra((0, 0), weighted, |p| *p == (4, 4)).unwrap().1, 8);
+        assert_eq!(dijkstra_all((0, 0), weighted)[&(0, 4)], 8);
+        assert_eq!(dfs((0, 0), open).count(), 18);
+    }
+
+    #[test]
+    fn ordering() {
+        let edges = [
+            ('C', 'A'),
+            ('C', 'F'),
+            ('A', 'B'),
+            ('A', 'D'),
+            ('B', 'E'),
+            ('D', 'E'),
+            ('F', 'E'),
+        ];
+        let order: String = topological_sort(None, edges.iter().cloned())
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(order, "CABDFE");
+        assert_eq!(topological_sort(None, vec![(1, 2), (2, 1)]), None);
+    }
+
+    #[test]
+    fn trees() {
+        let graph: Graph<u8> = vec![(1, 2), (1, 3), (2, 4), (2, 5), (3, 6), (7, 8)]
+            .into_iter()
+            .collect();
+        let parent = |n: &u8| graph.edges().find(|(_, to)| *to == n).map(|(p, _)| *p);
+        assert_eq!(lowest_common_ancestor(4, 6, parent), Some((1, 2, 2)));
+        assert_eq!(lowest_common_ancestor(4, 5, parent), Some((2, 1, 1)));
+        assert_eq!(lowest_common_ancestor(4, 8, parent), None);
+
+        let mut undirected = Graph::new();
+        for (a, b) in graph.edges() {
+            undirected.add_undirected_edge(*a, *b);
+        }
+        let mut nodes: Vec<u8> = undirected.nodes().cloned().collect();
+        nodes.sort();
+        let components = connected_components(nodes, |n| undirected.successors(n).to_vec());
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].len(), 6);
+    }
+}
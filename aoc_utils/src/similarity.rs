@@ -0,0 +1,164 @@
+//! Comparing words letter by letter.
+
+use std::collections::{BTreeMap, HashMap};
+
+/// The number of positions at which `a` and `b` differ, or `None` if they
+/// have different lengths.
+pub fn hamming(a: &str, b: &str) -> Option<usize> {
+    if a.chars().count() != b.chars().count() {
+        return None;
+    }
+    Some(a.chars().zip(b.chars()).filter(|(x, y)| x != y).count())
+}
+
+/// The letters `a` and `b` have in the same positions, in order.
+pub fn common(a: &str, b: &str) -> String {
+    a.chars()
+        .zip(b.chars())
+        .filter(|(x, y)| x == y)
+        .map(|(x, _)| x)
+        .collect()
+}
+
+/// Every pair of indices `(i, j)`, `i < j`, of equally long `words` that
+/// differ in exactly `k` positions, in increasing order.
+///
+/// Instead of comparing every pair, each choice of `k` positions is blanked
+/// out of every word in turn and words that then look the same are paired
+/// up. That takes time proportional to the number of words times the number
+/// of ways to choose `k` positions, which for small `k` is near-linear.
+pub fn differ_by<S: AsRef<str>>(words: &[S], k: usize) -> Vec<(usize, usize)> {
+    let words: Vec<Vec<char>> = words.iter().map(|w| w.as_ref().chars().collect()).collect();
+    let longest = words.iter().map(Vec::len).max().unwrap_or(0);
+    let mut pairs = Vec::new();
+    let mut seen: HashMap<(usize, Vec<Option<char>>), Vec<usize>> = HashMap::new();
+    for blanks in choose(longest, k) {
+        seen.clear();
+        for (i, word) in words.iter().enumerate() {
+            if blanks.last().is_some_and(|&last| last >= word.len()) {
+                continue;
+            }
+            let mut masked: Vec<Option<char>> = word.iter().copied().map(Some).collect();
+            for &blank in &blanks {
+                masked[blank] = None;
+            }
+            let twins = seen.entry((word.len(), masked)).or_default();
+            // Only pairs differing in every blanked position count, so each
+            // pair is found under exactly one choice of blanks.
+            for &j in twins.iter() {
+                if blanks.iter().all(|&b| words[j][b] != word[b]) {
+                    pairs.push((j, i));
+                }
+            }
+            twins.push(i);
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+/// Every way of picking `k` of `0..n`, each in increasing order.
+fn choose(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k > n {
+        return Vec::new();
+    }
+    let mut all = Vec::new();
+    let mut picked: Vec<usize> = (0..k).collect();
+    loop {
+        all.push(picked.clone());
+        // Advance the rightmost pick that still has room to move.
+        match (0..k).rev().find(|&i| picked[i] < n - k + i) {
+            Some(i) => {
+                picked[i] += 1;
+                for j in i + 1..k {
+                    picked[j] = picked[j - 1] + 1;
+                }
+            }
+            None => return all,
+        }
+    }
+}
+
+/// How often each letter occurs in a word, regardless of order, so anagrams
+/// share a signature.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Signature {
+    counts: BTreeMap<char, usize>,
+}
+
+impl Signature {
+    pub fn new(word: &str) -> Self {
+        let mut counts = BTreeMap::new();
+        for c in word.chars() {
+            *counts.entry(c).or_insert(0) += 1;
+        }
+        Signature { counts }
+    }
+
+    /// How many times `letter` occurs.
+    pub fn count(&self, letter: char) -> usize {
+        self.counts.get(&letter).copied().unwrap_or(0)
+    }
+
+    /// Whether some letter occurs exactly `n` times.
+    pub fn has_count(&self, n: usize) -> bool {
+        self.counts.values().any(|&count| count == n)
+    }
+
+    /// The letters with their counts, in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = (char, usize)> + '_ {
+        self.counts.iter().map(|(&c, &n)| (c, n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        assert_eq!(hamming("fghij", "fguij"), Some(1));
+        assert_eq!(hamming("abcde", "axcye"), Some(2));
+        assert_eq!(hamming("abc", "abcd"), None);
+        assert_eq!(common("fghij", "fguij"), "fgij");
+        assert_eq!(choose(4, 2).len(), 6);
+        assert_eq!(choose(3, 0), [Vec::<usize>::new()]);
+        assert!(choose(2, 3).is_empty());
+    }
+
+    #[test]
+    fn pairs() {
+        let words = [
+            "abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz",
+        ];
+        assert_eq!(differ_by(&words, 1), [(1, 4)]);
+        assert_eq!(differ_by(&words, 2), [(0, 5)]);
+        for k in 0..=5 {
+            let mut brute = Vec::new();
+            for i in 0..words.len() {
+                for j in i + 1..words.len() {
+                    if hamming(words[i], words[j]) == Some(k) {
+                        brute.push((i, j));
+                    }
+                }
+            }
+            assert_eq!(differ_by(&words, k), brute, "k = {}", k);
+        }
+        assert_eq!(differ_by(&["ab", "ab", "abc", "abd"], 0), [(0, 1)]);
+        assert_eq!(differ_by(&["ab", "ab", "abc", "abd"], 1), [(2, 3)]);
+        assert!(differ_by::<&str>(&[], 1).is_empty());
+    }
+
+    #[test]
+    fn signatures() {
+        let signature = Signature::new("bababc");
+        assert_eq!(signature.count('a'), 2);
+        assert_eq!(signature.count('z'), 0);
+        assert!(signature.has_count(1) && signature.has_count(3) && !signature.has_count(4));
+        assert_eq!(Signature::new("listen"), Signature::new("silent"));
+        assert_eq!(
+            signature.iter().collect::<Vec<_>>(),
+            [('a', 2), ('b', 3), ('c', 1)]
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! Summed-area tables: after one pass over a grid, the sum of any rectangle
+//! of it takes four lookups.
+
+use crate::grid::{Grid, Pos};
+use std::ops::{Add, Sub};
+
+/// Entry `(x, y)` holds the sum of every cell above and to the left of
+/// `(x, y)`, exclusive, so the table is one larger than the grid each way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SummedAreaTable<T> {
+    sums: Grid<T>,
+}
+
+impl<T> SummedAreaTable<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    pub fn new(grid: &Grid<T>) -> Self {
+        Self::from_fn(grid.width(), grid.height(), |pos| grid[pos])
+    }
+
+    /// The table of a `width` by `height` grid whose cells are `f(pos)`.
+    pub fn from_fn<F>(width: usize, height: usize, mut f: F) -> Self
+    where
+        F: FnMut(Pos) -> T,
+    {
+        let mut sums = Grid::new(width + 1, height + 1, T::default());
+        for y in 0..height {
+            for x in 0..width {
+                sums[(x + 1, y + 1)] =
+                    f((x, y)) + sums[(x, y + 1)] + sums[(x + 1, y)] - sums[(x, y)];
+            }
+        }
+        SummedAreaTable { sums }
+    }
+
+    /// The width of the summed grid.
+    pub fn width(&self) -> usize {
+        self.sums.width() - 1
+    }
+
+    /// The height of the summed grid.
+    pub fn height(&self) -> usize {
+        self.sums.height() - 1
+    }
+
+    /// The sum of the `width` by `height` rectangle whose top left cell is
+    /// `(x, y)`. Panics if the rectangle sticks out of the grid.
+    pub fn sum(&self, (x, y): Pos, width: usize, height: usize) -> T {
+        let (x1, y1) = (x + width, y + height);
+        self.sums[(x1, y1)] + self.sums[(x, y)] - self.sums[(x, y1)] - self.sums[(x1, y)]
+    }
+
+    /// The top left corners of every `width` by `height` rectangle that fits,
+    /// with its sum.
+    pub fn rectangles(&self, width: usize, height: usize) -> impl Iterator<Item = (Pos, T)> + '_ {
+        let xs = (self.width() + 1).saturating_sub(width);
+        let ys = (self.height() + 1).saturating_sub(height);
+        (0..ys).flat_map(move |y| (0..xs).map(move |x| ((x, y), self.sum((x, y), width, height))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums() {
+        let grid = Grid::from_fn(4, 3, |(x, y)| (x + 10 * y) as i64);
+        let table = SummedAreaTable::new(&grid);
+        assert_eq!((table.width(), table.height()), (4, 3));
+        assert_eq!(table.sum((0, 0), 4, 3), grid.iter().sum::<i64>());
+        assert_eq!(table.sum((1, 1), 2, 2), 11 + 12 + 21 + 22);
+        assert_eq!(table.sum((3, 2), 1, 1), 23);
+        assert_eq!(table.sum((2, 0), 0, 3), 0);
+
+        let best = table.rectangles(2, 2).max_by_key(|&(_, sum)| sum);
+        assert_eq!(best, Some(((2, 1), 12 + 13 + 22 + 23)));
+        assert_eq!(table.rectangles(5, 1).count(), 0);
+    }
+}
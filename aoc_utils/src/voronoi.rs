@@ -0,0 +1,520 @@
+//! Splits the plane into regions by nearest seed point.
+//!
+//! Regions are only worked out inside a window around the seeds, but finite
+//! regions always fit in it whole. With Manhattan and Chebyshev distance
+//! every cell outside the window has the same closest seeds as some cell on
+//! its edge, so a region is unbounded exactly when it reaches that edge. With
+//! Euclidean distance a finite region next to a long flat edge of the seeds'
+//! convex hull can stick out of any fixed window, so there a region is
+//! unbounded exactly when its seed lies on the hull's boundary instead.
+
+use crate::{
+    grid::{Grid, Pos},
+    image,
+};
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+};
+
+/// How far apart two cells are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Metric {
+    /// Steps along the axes.
+    Manhattan,
+    /// Steps along the axes or diagonals.
+    Chebyshev,
+    /// Straight-line distance.
+    Euclidean,
+}
+
+impl Metric {
+    pub fn distance(self, a: Pos, b: Pos) -> f64 {
+        match self {
+            Metric::Euclidean => (self.rank(a, b) as f64).sqrt(),
+            _ => self.rank(a, b) as f64,
+        }
+    }
+
+    /// An exact integer ordered like the distance: the distance itself, or
+    /// its square for Euclidean distance.
+    fn rank(self, (ax, ay): Pos, (bx, by): Pos) -> u64 {
+        let (dx, dy) = (ax.abs_diff(bx) as u64, ay.abs_diff(by) as u64);
+        match self {
+            Metric::Manhattan => dx + dy,
+            Metric::Chebyshev => dx.max(dy),
+            Metric::Euclidean => dx * dx + dy * dy,
+        }
+    }
+}
+
+/// Which seed every cell near the seeds is closest to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Regions {
+    origin: (isize, isize),
+    /// The closest seed of each cell of the window, `None` on ties.
+    owners: Grid<Option<usize>>,
+    unbounded: Vec<bool>,
+}
+
+impl Regions {
+    /// Manhattan and Chebyshev regions grow out of all seeds at once with a
+    /// breadth-first search, which is exact as a step towards a cell's closest
+    /// seed takes it at most one step closer to any other. Euclidean regions
+    /// need not be connected on the grid, so there every cell compares all
+    /// seeds instead.
+    ///
+    /// The window is one cell wider than the bounding box for Manhattan
+    /// distance, and the box's longer side plus one wider for Chebyshev
+    /// distance: past that, moving straight or diagonally away from the box
+    /// keeps the same closest seeds. For Euclidean distance it also takes in
+    /// every corner of a finite region, so those are always fully inside it.
+    pub fn new(seeds: &[Pos], metric: Metric) -> Self {
+        let ((left, top), (right, bottom)) = bounds(seeds);
+        let hull = on_hull(seeds);
+        let (mut low, mut high) = (
+            (left as isize, top as isize),
+            (right as isize, bottom as isize),
+        );
+        let margin = match metric {
+            Metric::Chebyshev => ((right - left).max(bottom - top) + 1) as isize,
+            _ => 1,
+        };
+        if metric == Metric::Euclidean {
+            for (x, y) in corners(seeds, &hull) {
+                low = (low.0.min(x.floor() as isize), low.1.min(y.floor() as isize));
+                high = (high.0.max(x.ceil() as isize), high.1.max(y.ceil() as isize));
+            }
+        }
+        let origin = (low.0 - margin, low.1 - margin);
+        let width = (high.0 - low.0 + 1 + 2 * margin) as usize;
+        let height = (high.1 - low.1 + 1 + 2 * margin) as usize;
+        let local = |(x, y): Pos| {
+            (
+                (x as isize - origin.0) as usize,
+                (y as isize - origin.1) as usize,
+            )
+        };
+
+        let owners = match metric {
+            Metric::Euclidean => Grid::from_fn(width, height, |cell| {
+                let ranks: Vec<u64> = seeds.iter().map(|&s| metric.rank(local(s), cell)).collect();
+                let nearest = ranks.iter().min()?;
+                let mut closest = (0..seeds.len()).filter(|&i| ranks[i] == *nearest);
+                let seed = closest.next();
+                closest.next().map_or(seed, |_| None)
+            }),
+            _ => {
+                let local_seeds: Vec<Pos> = seeds.iter().map(|&s| local(s)).collect();
+                spread(width, height, &local_seeds, metric == Metric::Chebyshev)
+            }
+        };
+
+        let unbounded = match metric {
+            Metric::Euclidean => hull,
+            _ => touching_border(&owners, seeds.len()),
+        };
+        Regions {
+            origin,
+            owners,
+            unbounded,
+        }
+    }
+
+    /// Where the window's top left cell is, which may be left of or above
+    /// `(0, 0)`.
+    pub fn origin(&self) -> (isize, isize) {
+        self.origin
+    }
+
+    /// The closest seed of every cell in the window, `None` where two or
+    /// more seeds are equally close.
+    pub fn owners(&self) -> &Grid<Option<usize>> {
+        &self.owners
+    }
+
+    /// The closest seed to `(x, y)`, if it is unique and inside the window.
+    pub fn owner(&self, (x, y): (isize, isize)) -> Option<usize> {
+        let (x, y) = (x - self.origin.0, y - self.origin.1);
+        if x < 0 || y < 0 {
+            return None;
+        }
+        *self.owners.get((x as usize, y as usize))?
+    }
+
+    pub fn is_unbounded(&self, seed: usize) -> bool {
+        self.unbounded[seed]
+    }
+
+    /// The number of cells closest to each seed, `None` for unbounded
+    /// regions.
+    pub fn areas(&self) -> Vec<Option<usize>> {
+        let mut areas = vec![0; self.unbounded.len()];
+        for &seed in self.owners.iter().flatten() {
+            areas[seed] += 1;
+        }
+        areas
+            .into_iter()
+            .zip(&self.unbounded)
+            .map(|(area, &unbounded)| (!unbounded).then_some(area))
+            .collect()
+    }
+
+    /// The seed with the largest finite region, and its area.
+    pub fn largest_bounded(&self) -> Option<(usize, usize)> {
+        self.areas()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(seed, area)| Some((seed, area?)))
+            .max_by_key(|&(_, area)| area)
+    }
+
+    /// Writes the window as a PPM image with one color per region, unbounded
+    /// regions darker, and ties in black.
+    pub fn write_ppm<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        image::write_ppm(out, &self.owners, |owner| match *owner {
+            Some(seed) => {
+                let value = if self.unbounded[seed] { 0.5 } else { 0.9 };
+                hsv(seed as f64 * 137.508, 0.6, value)
+            }
+            None => [0, 0, 0],
+        })
+    }
+}
+
+/// Grows regions out of `seeds` one step at a time, spreading to the eight
+/// surrounding cells if `diagonal`, else to the four adjacent ones. A cell
+/// reached at the same step from different regions is a tie, and so is
+/// anything only reached through a tie.
+fn spread(width: usize, height: usize, seeds: &[Pos], diagonal: bool) -> Grid<Option<usize>> {
+    let mut cells: Grid<Option<(usize, Option<usize>)>> = Grid::new(width, height, None);
+    let mut queue = VecDeque::new();
+    for (i, &seed) in seeds.iter().enumerate() {
+        match cells[seed] {
+            None => {
+                cells[seed] = Some((0, Some(i)));
+                queue.push_back(seed);
+            }
+            Some(_) => cells[seed] = Some((0, None)),
+        }
+    }
+    while let Some(pos) = queue.pop_front() {
+        let (steps, owner) = cells[pos].unwrap();
+        let next: Vec<Pos> = if diagonal {
+            cells.neighbors8(pos).collect()
+        } else {
+            cells.neighbors4(pos).collect()
+        };
+        for n in next {
+            match cells[n] {
+                None => {
+                    cells[n] = Some((steps + 1, owner));
+                    queue.push_back(n);
+                }
+                Some((s, other)) if s == steps + 1 && other != owner => {
+                    cells[n] = Some((s, None));
+                }
+                _ => {}
+            }
+        }
+    }
+    cells.map(|cell| cell.and_then(|(_, owner)| owner))
+}
+
+/// The number of cells whose distances to all of `seeds` add up to less
+/// than `threshold`.
+pub fn within(seeds: &[Pos], metric: Metric, threshold: f64) -> usize {
+    if seeds.is_empty() {
+        return 0;
+    }
+    // Every step away from the bounding box adds at least one per seed.
+    let margin = (threshold / seeds.len() as f64).ceil().max(0.0) as usize;
+    let ((left, top), (right, bottom)) = bounds(seeds);
+    let width = right - left + 1 + 2 * margin;
+    let height = bottom - top + 1 + 2 * margin;
+    let seeds: Vec<Pos> = seeds
+        .iter()
+        .map(|&(x, y)| (x - left + margin, y - top + margin))
+        .collect();
+
+    if metric == Metric::Manhattan {
+        // Manhattan distance splits into independent x and y sums.
+        let total = |axis: fn(&Pos) -> usize, at: usize| {
+            seeds.iter().map(|s| axis(s).abs_diff(at)).sum::<usize>() as f64
+        };
+        let rows: Vec<f64> = (0..height).map(|y| total(|s| s.1, y)).collect();
+        return (0..width)
+            .map(|x| total(|s| s.0, x))
+            .map(|column| rows.iter().filter(|&&row| column + row < threshold).count())
+            .sum();
+    }
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&cell| {
+            let total: f64 = seeds.iter().map(|&s| metric.distance(s, cell)).sum();
+            total < threshold
+        })
+        .count()
+}
+
+/// Which seeds own a cell on the edge of the window.
+fn touching_border(owners: &Grid<Option<usize>>, seeds: usize) -> Vec<bool> {
+    let (width, height) = (owners.width(), owners.height());
+    let mut unbounded = vec![false; seeds];
+    let border = (0..width)
+        .flat_map(|x| [(x, 0), (x, height - 1)])
+        .chain((0..height).flat_map(|y| [(0, y), (width - 1, y)]));
+    for pos in border {
+        if let Some(seed) = owners[pos] {
+            unbounded[seed] = true;
+        }
+    }
+    unbounded
+}
+
+/// The centers of the circles through three seeds with no seed inside
+/// them, where Euclidean regions meet at a corner, leaving out those where
+/// all three seeds are on the hull. Those only touch unbounded regions and
+/// can be arbitrarily far away.
+fn corners(seeds: &[Pos], hull: &[bool]) -> Vec<(f64, f64)> {
+    let points: Vec<(f64, f64)> = seeds.iter().map(|&(x, y)| (x as f64, y as f64)).collect();
+    let mut corners = Vec::new();
+    for (i, &a) in points.iter().enumerate() {
+        for (j, &b) in points.iter().enumerate().skip(i + 1) {
+            for (k, &c) in points.iter().enumerate().skip(j + 1) {
+                if hull[i] && hull[j] && hull[k] {
+                    continue;
+                }
+                let d = 2.0 * (a.0 * (b.1 - c.1) + b.0 * (c.1 - a.1) + c.0 * (a.1 - b.1));
+                if d == 0.0 {
+                    continue;
+                }
+                let norm = |p: (f64, f64)| p.0 * p.0 + p.1 * p.1;
+                let x = (norm(a) * (b.1 - c.1) + norm(b) * (c.1 - a.1) + norm(c) * (a.1 - b.1)) / d;
+                let y = (norm(a) * (c.0 - b.0) + norm(b) * (a.0 - c.0) + norm(c) * (b.0 - a.0)) / d;
+                let radius = norm((a.0 - x, a.1 - y));
+                let empty = points
+                    .iter()
+                    .all(|&p| norm((p.0 - x, p.1 - y)) >= radius * (1.0 - 1e-9));
+                if empty {
+                    corners.push((x, y));
+                }
+            }
+        }
+    }
+    corners
+}
+
+/// Which seeds lie on the boundary of the seeds' convex hull, corners and
+/// edges alike. If all seeds are on one line, that is every one of them.
+fn on_hull(seeds: &[Pos]) -> Vec<bool> {
+    let point = |&(x, y): &Pos| (x as i64, y as i64);
+    let cross = |o: (i64, i64), a: (i64, i64), b: (i64, i64)| {
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    };
+    let mut points: Vec<(i64, i64)> = seeds.iter().map(point).collect();
+    points.sort_unstable();
+    points.dedup();
+    // Andrew's monotone chain, keeping only the corners.
+    let mut hull: Vec<(i64, i64)> = Vec::new();
+    for pass in 0..2 {
+        let start = hull.len();
+        for &p in &points {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0
+            {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+        if pass == 0 {
+            points.reverse();
+        }
+    }
+    if hull.len() < 3 {
+        return vec![true; seeds.len()];
+    }
+    seeds
+        .iter()
+        .map(|s| {
+            let p = point(s);
+            (0..hull.len()).any(|i| {
+                let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+                cross(a, b, p) == 0
+                    && (a.0.min(b.0)..=a.0.max(b.0)).contains(&p.0)
+                    && (a.1.min(b.1)..=a.1.max(b.1)).contains(&p.1)
+            })
+        })
+        .collect()
+}
+
+/// The top left and bottom right corners of the seeds' bounding box.
+fn bounds(seeds: &[Pos]) -> (Pos, Pos) {
+    let left = seeds.iter().map(|s| s.0).min().unwrap_or(0);
+    let top = seeds.iter().map(|s| s.1).min().unwrap_or(0);
+    let right = seeds.iter().map(|s| s.0).max().unwrap_or(0);
+    let bottom = seeds.iter().map(|s| s.1).max().unwrap_or(0);
+    ((left, top), (right, bottom))
+}
+
+/// Converts a color from hue (degrees), saturation and value to RGB.
+fn hsv(hue: f64, saturation: f64, value: f64) -> [u8; 3] {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    [r, g, b].map(|c| ((c + m) * 255.0).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEEDS: [Pos; 6] = [(1, 1), (1, 6), (8, 3), (3, 4), (5, 5), (8, 9)];
+
+    #[test]
+    fn manhattan() {
+        let regions = Regions::new(&SEEDS, Metric::Manhattan);
+        assert_eq!(regions.origin(), (0, 0));
+        assert_eq!(regions.areas(), [None, None, None, Some(9), Some(17), None]);
+        assert_eq!(regions.largest_bounded(), Some((4, 17)));
+        assert_eq!(regions.owner((0, 0)), Some(0));
+        assert_eq!(regions.owner((0, 4)), None);
+        assert_eq!(regions.owner((-1, 0)), None);
+        assert_eq!(within(&SEEDS, Metric::Manhattan, 32.0), 16);
+    }
+
+    #[test]
+    fn other_metrics() {
+        for metric in [Metric::Chebyshev, Metric::Euclidean] {
+            let regions = Regions::new(&SEEDS, metric);
+            for (seed, &(x, y)) in SEEDS.iter().enumerate() {
+                assert_eq!(regions.owner((x as isize, y as isize)), Some(seed));
+            }
+            // Only the seeds on the corners of the hull reach infinity.
+            let unbounded: Vec<bool> = (0..6).map(|s| regions.is_unbounded(s)).collect();
+            assert_eq!(unbounded, [true, true, true, false, false, true]);
+        }
+        let brute = |metric: Metric, threshold: f64| {
+            let mut count = 0;
+            for y in 0..40 {
+                for x in 0..40 {
+                    let shifted: Vec<Pos> = SEEDS.iter().map(|&(a, b)| (a + 15, b + 15)).collect();
+                    let total: f64 = shifted.iter().map(|&s| metric.distance(s, (x, y))).sum();
+                    count += usize::from(total < threshold);
+                }
+            }
+            count
+        };
+        for metric in [Metric::Manhattan, Metric::Chebyshev, Metric::Euclidean] {
+            assert_eq!(within(&SEEDS, metric, 40.0), brute(metric, 40.0));
+        }
+    }
+
+    #[test]
+    fn flat_hull() {
+        // The third seed is inside the hull, but its Euclidean region
+        // reaches far beyond the bounding box.
+        let seeds = [(14, 3), (24, 26), (26, 17), (29, 4)];
+        let regions = Regions::new(&seeds, Metric::Euclidean);
+        assert!(!regions.is_unbounded(2));
+        // Count over the window and a wide band around it.
+        let (x0, y0) = (
+            regions.origin().0 as i64 - 50,
+            regions.origin().1 as i64 - 50,
+        );
+        let (width, height) = (
+            regions.owners().width() as i64 + 100,
+            regions.owners().height() as i64 + 100,
+        );
+        let brute = (y0..y0 + height)
+            .flat_map(|y| (x0..x0 + width).map(move |x| (x, y)))
+            .filter(|&(x, y): &(i64, i64)| {
+                let rank = |&(sx, sy): &Pos| (x - sx as i64).pow(2) + (y - sy as i64).pow(2);
+                seeds
+                    .iter()
+                    .all(|s| s == &seeds[2] || rank(s) > rank(&seeds[2]))
+            })
+            .count();
+        assert_eq!(regions.areas()[2], Some(brute));
+        for seed in [0, 1, 3] {
+            assert!(regions.is_unbounded(seed));
+        }
+        assert_eq!(on_hull(&[(0, 0), (2, 0), (4, 0)]), [true; 3]);
+        assert_eq!(
+            on_hull(&[(0, 0), (4, 0), (2, 0), (2, 2), (0, 4), (1, 1)]),
+            [true, true, true, true, true, false]
+        );
+    }
+
+    #[test]
+    fn wide_window() {
+        // Compare against every seed for each cell of a much wider window.
+        let sets: [&[Pos]; 3] = [
+            &SEEDS,
+            &[(0, 0), (9, 2), (4, 4), (5, 5), (2, 7), (7, 9), (3, 1)],
+            &[(2, 0), (0, 5), (6, 6), (3, 3), (4, 2)],
+        ];
+        for seeds in sets {
+            for metric in [Metric::Manhattan, Metric::Chebyshev] {
+                let regions = Regions::new(seeds, metric);
+                let (mut areas, mut edge) = (vec![0; seeds.len()], vec![false; seeds.len()]);
+                for y in -40..50_i64 {
+                    for x in -40..50_i64 {
+                        let rank = |&(sx, sy): &Pos| {
+                            let (dx, dy) = ((x - sx as i64).abs(), (y - sy as i64).abs());
+                            match metric {
+                                Metric::Manhattan => dx + dy,
+                                _ => dx.max(dy),
+                            }
+                        };
+                        let nearest = seeds.iter().map(rank).min().unwrap();
+                        let closest: Vec<usize> = (0..seeds.len())
+                            .filter(|&i| rank(&seeds[i]) == nearest)
+                            .collect();
+                        if let [seed] = closest[..] {
+                            areas[seed] += 1;
+                            edge[seed] |= x == -40 || x == 49 || y == -40 || y == 49;
+                        }
+                    }
+                }
+                let expected: Vec<Option<usize>> = areas
+                    .into_iter()
+                    .zip(edge)
+                    .map(|(area, edge)| (!edge).then_some(area))
+                    .collect();
+                assert_eq!(regions.areas(), expected, "{:?} {:?}", metric, seeds);
+            }
+        }
+
+        // The corner where these three meet is far below them, but only
+        // touches unbounded regions.
+        let regions = Regions::new(&[(0, 0), (100, 1), (200, 0)], Metric::Euclidean);
+        assert_eq!(regions.owners().height(), 4);
+        assert_eq!(regions.areas(), [None, None, None]);
+    }
+
+    #[test]
+    fn edge_cases() {
+        let regions = Regions::new(&[(0, 0), (0, 0), (2, 0)], Metric::Manhattan);
+        assert_eq!(regions.owner((0, 0)), None);
+        assert_eq!(regions.owner((3, 0)), Some(2));
+        assert_eq!(regions.owner((1, 0)), None);
+        assert_eq!(within(&[], Metric::Euclidean, 10.0), 0);
+
+        let mut ppm = Vec::new();
+        regions.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n5 3\n255\n"));
+        assert_eq!(hsv(0.0, 1.0, 1.0), [255, 0, 0]);
+        assert_eq!(hsv(240.0, 1.0, 1.0), [0, 0, 255]);
+    }
+}